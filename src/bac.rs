@@ -0,0 +1,225 @@
+//! Basic Access Control (BAC) key derivation as defined in
+//! [ICAO Document 9303 Part 11](https://www.icao.int/publications/Documents/9303_p11_cons_en.pdf) (sections 4.3 and 9.7).
+
+use crate::document::{Document, Field};
+use crate::encoder::{self, check_digit, date, field};
+use crate::error::Error;
+use crate::raw::RawMrz;
+use sha1::{Digest, Sha1};
+
+/// Keys derived from the MRZ for Basic Access Control
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Keys {
+    /// Key seed (K_seed)
+    pub seed: [u8; 16],
+    /// Two-key 3DES encryption key (K_enc)
+    pub enc: [u8; 16],
+    /// Two-key 3DES MAC key (K_mac)
+    pub mac: [u8; 16],
+}
+
+const ENC_COUNTER: u32 = 1;
+const MAC_COUNTER: u32 = 2;
+
+/// MRZ information used as the BAC key: document number, date of birth and date of
+/// expiry, each followed by its check digit.
+/// Document numbers longer than nine characters are used in full.
+/// The document number is taken as it appears in the MRZ when kept while parsing
+/// (see [`ParseOptions::keep_raw`](crate::ParseOptions::keep_raw)). Otherwise it is
+/// rebuilt from [`Document::document_number`], which has lost any fillers within it.
+pub fn mrz_information(document: &Document) -> Result<String, Error> {
+    let number = match document.raw().and_then(document_number) {
+        Some(number) => number,
+        None => {
+            let number = document.document_number();
+            let number = field(number, number.len().max(9))?;
+            let check_digit = check_digit(&number)?;
+            format!("{}{}", number, check_digit)
+        }
+    };
+    let birth_date = encoder::birth_date(document.birth_date(), &document.birth_date_partial())?;
+    let expiry_date = date(&document.expiry_date());
+
+    Ok(format!(
+        "{}{}{}{}{}",
+        number,
+        birth_date,
+        check_digit(&birth_date)?,
+        expiry_date,
+        check_digit(&expiry_date)?
+    ))
+}
+
+// Document number followed by its check digit, as they appear in the MRZ
+fn document_number(raw: &RawMrz) -> Option<String> {
+    match raw.field(Field::DocumentNumber).collect::<Vec<_>>()[..] {
+        [number, check_digit] => Some(format!("{}{}", raw.text(number), raw.text(check_digit))),
+        // Longer numbers continue in the optional data, after a filler in place of the check digit
+        [number, extension, check_digit] => {
+            let number = raw.text(number);
+            Some(format!(
+                "{}{}{}",
+                &number[..number.len() - 1],
+                raw.text(extension),
+                raw.text(check_digit)
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Key seed: the 16 most significant bytes of the SHA-1 hash of the MRZ information
+pub fn key_seed(mrz_information: &str) -> [u8; 16] {
+    let mut seed = [0; 16];
+    seed.copy_from_slice(&Sha1::digest(mrz_information.as_bytes())[..16]);
+    seed
+}
+
+/// Derive a two-key 3DES key from the key seed and a counter (1 for K_enc, 2 for K_mac),
+/// with the parity bits adjusted
+pub fn derive_key(seed: &[u8], counter: u32) -> [u8; 16] {
+    let hash = Sha1::new()
+        .chain_update(seed)
+        .chain_update(counter.to_be_bytes())
+        .finalize();
+
+    let mut key = [0; 16];
+    key.copy_from_slice(&hash[..16]);
+    adjust_parity(&mut key);
+    key
+}
+
+/// Derive the BAC keys from a document
+pub fn keys(document: &Document) -> Result<Keys, Error> {
+    let seed = key_seed(&mrz_information(document)?);
+
+    Ok(Keys {
+        seed,
+        enc: derive_key(&seed, ENC_COUNTER),
+        mac: derive_key(&seed, MAC_COUNTER),
+    })
+}
+
+// DES keys use the least significant bit of every byte for odd parity
+pub(crate) fn adjust_parity(key: &mut [u8]) {
+    for byte in key {
+        let bits = *byte & 0xfe;
+        *byte = if bits.count_ones() % 2 == 0 {
+            bits | 1
+        } else {
+            bits
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::options::ParseOptions;
+    use crate::parser::{parse, parse_with_options};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+    }
+
+    // Worked example from ICAO 9303 Part 11, Appendix D.2
+    #[test]
+    fn icao_example() {
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C<3UTO6908061F9406236ZE184226B<<<<<14",
+            true,
+        )
+        .unwrap();
+
+        let mrz_information = mrz_information(&document).unwrap();
+        assert_eq!(mrz_information, "L898902C<369080619406236");
+
+        let keys = keys(&document).unwrap();
+        assert_eq!(hex(&keys.seed), "239AB9CB282DAF66231DC5A4DF6BFBAE");
+        assert_eq!(hex(&keys.enc), "AB94FDECF2674FDFB9B391F85D7F76F2");
+        assert_eq!(hex(&keys.mac), "7962D9ECE03D1ACD4C76089DCE131543");
+    }
+
+    #[test]
+    fn long_document_number() {
+        let document = parse(
+            "I<UTOD23145890<7349<ABC123<<<<\
+             3407127M9507122UTO<<<<<<<<<<<3\
+             STEVENSON<<PETER<JOHN<<<<<<<<<",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            mrz_information(&document).unwrap(),
+            "D23145890734934071279507122"
+        );
+
+        let options = ParseOptions {
+            keep_raw: true,
+            ..ParseOptions::default()
+        };
+        let document = parse_with_options(
+            "I<UTOD23145890<7349<ABC123<<<<\
+             3407127M9507122UTO<<<<<<<<<<<3\
+             STEVENSON<<PETER<JOHN<<<<<<<<<",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            mrz_information(&document).unwrap(),
+            "D23145890734934071279507122"
+        );
+    }
+
+    #[test]
+    fn document_number_with_filler() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   AB<1234568UTO7408122F1204159ZE184226B<<<<<16";
+        let options = ParseOptions {
+            keep_raw: true,
+            ..ParseOptions::default()
+        };
+
+        let document = parse_with_options(mrz, &options).unwrap();
+        assert_eq!(document.document_number(), "AB123456");
+        assert_eq!(
+            mrz_information(&document).unwrap(),
+            "AB<123456874081221204159"
+        );
+
+        // Without the MRZ as read, the filler is lost
+        let document = parse(mrz, true).unwrap();
+        assert_eq!(
+            mrz_information(&document).unwrap(),
+            "AB123456<474081221204159"
+        );
+    }
+
+    #[test]
+    fn inconsistent_birth_date() {
+        let mut document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C<3UTO6908061F9406236ZE184226B<<<<<14",
+            true,
+        )
+        .unwrap();
+
+        if let Document::Passport(passport) = &mut document {
+            passport.birth_date = passport.birth_date.succ_opt().unwrap();
+        }
+        assert_eq!(
+            mrz_information(&document).unwrap_err().kind(),
+            ErrorKind::InvalidBirthDate
+        );
+    }
+
+    #[test]
+    fn parity() {
+        let mut key = [0x00, 0x01, 0x02, 0x03, 0xfe, 0xff];
+        adjust_parity(&mut key);
+        assert_eq!(key, [0x01, 0x01, 0x02, 0x02, 0xfe, 0xfe]);
+    }
+}
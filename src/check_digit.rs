@@ -0,0 +1,130 @@
+//! Check digit calculation as defined in
+//! [ICAO Document 9303 Part 3](https://www.icao.int/publications/Documents/9303_p3_cons_en.pdf) (section 4.9).
+
+use crate::error::{Error, ErrorKind};
+
+/// Compute the check digit for an MRZ field.
+/// Fails with [`ErrorKind::InvalidChar`] if the field contains characters other than [A-Z], [0-9] or <.
+pub fn compute_check_digit(field: &str) -> Result<u8, Error> {
+    check_digit_of(field.char_indices())
+        .map_err(|index| Error::at(ErrorKind::InvalidChar, None, field, index))
+}
+
+// Check digit of a sequence of indexed characters, which may span several fields.
+// Fails with the index of the first invalid character.
+pub(crate) fn check_digit_of(chars: impl Iterator<Item = (usize, char)>) -> Result<u8, usize> {
+    let mut sum = 0;
+
+    for ((index, c), weighting) in chars.zip([7, 3, 1].iter().cycle()) {
+        sum += value(c).ok_or(index)? * weighting;
+    }
+
+    Ok((sum % 10) as u8)
+}
+
+fn value(c: char) -> Option<u32> {
+    match c {
+        '0' => Some(0),
+        '1' => Some(1),
+        '2' => Some(2),
+        '3' => Some(3),
+        '4' => Some(4),
+        '5' => Some(5),
+        '6' => Some(6),
+        '7' => Some(7),
+        '8' => Some(8),
+        '9' => Some(9),
+        'A' => Some(10),
+        'B' => Some(11),
+        'C' => Some(12),
+        'D' => Some(13),
+        'E' => Some(14),
+        'F' => Some(15),
+        'G' => Some(16),
+        'H' => Some(17),
+        'I' => Some(18),
+        'J' => Some(19),
+        'K' => Some(20),
+        'L' => Some(21),
+        'M' => Some(22),
+        'N' => Some(23),
+        'O' => Some(24),
+        'P' => Some(25),
+        'Q' => Some(26),
+        'R' => Some(27),
+        'S' => Some(28),
+        'T' => Some(29),
+        'U' => Some(30),
+        'V' => Some(31),
+        'W' => Some(32),
+        'X' => Some(33),
+        'Y' => Some(34),
+        'Z' => Some(35),
+        '<' => Some(0),
+        _ => None,
+    }
+}
+
+/// Verify an MRZ field against its check digit.
+/// A filler (<) is accepted in place of the check digit when the field is empty
+/// (consists only of fillers), as used for unused optional data fields.
+pub fn verify(field: &str, check_digit: char) -> Result<(), Error> {
+    if check_digit == '<' {
+        return if field.chars().all(|c| c == '<') {
+            Ok(())
+        } else {
+            Err(ErrorKind::BadCheckDigit.into())
+        };
+    }
+
+    let check_digit = check_digit.to_digit(10).ok_or(ErrorKind::ExpectedDigit)?;
+    verify_check_digit(field, check_digit)
+}
+
+pub(crate) fn verify_check_digit(slice: &str, check_digit: u32) -> Result<(), Error> {
+    if check_digit == u32::from(compute_check_digit(slice)?) {
+        Ok(())
+    } else {
+        Err(ErrorKind::BadCheckDigit.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute() {
+        assert_eq!(compute_check_digit("L898902C3").unwrap(), 6);
+        assert_eq!(compute_check_digit("740812").unwrap(), 2);
+        assert_eq!(compute_check_digit("ZE184226B<<<<<").unwrap(), 1);
+        assert_eq!(compute_check_digit("<<<<<<<<<").unwrap(), 0);
+        assert_eq!(
+            compute_check_digit("L898902c3").unwrap_err().kind(),
+            ErrorKind::InvalidChar
+        );
+    }
+
+    #[test]
+    fn verify_field() {
+        verify("L898902C3", '6').unwrap();
+        assert_eq!(
+            verify("L898902C3", '7').unwrap_err().kind(),
+            ErrorKind::BadCheckDigit
+        );
+        assert_eq!(
+            verify("L898902C3", 'X').unwrap_err().kind(),
+            ErrorKind::ExpectedDigit
+        );
+    }
+
+    #[test]
+    fn verify_empty_field() {
+        verify("<<<<<<<<<<<<<<", '<').unwrap();
+        verify("<<<<<<<<<<<<<<", '0').unwrap();
+        assert_eq!(
+            verify("ZE184226B<<<<<", '<').unwrap_err().kind(),
+            ErrorKind::BadCheckDigit
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::check_digit::verify_check_digit;
+use crate::document::{Document, Format};
+use crate::error::{Error, ErrorKind};
+use crate::input;
+use crate::parser;
+use std::ops::Range;
+
+/// Character substitution applied while correcting an MRZ
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Correction {
+    /// Position of the character within the concatenated MRZ
+    pub position: usize,
+    /// Character as read
+    pub from: char,
+    /// Character after correction
+    pub to: char,
+}
+
+/// Document parsed from an MRZ after correcting likely OCR errors
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CorrectedDocument {
+    /// Parsed document
+    pub document: Document,
+    /// Corrected MRZ (concatenated)
+    pub mrz: String,
+    /// Substitutions applied to the original MRZ
+    pub corrections: Vec<Correction>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Class {
+    // Letters and fillers only: document code, country codes, sex and names
+    Alpha,
+    // Digits only: dates and check digits
+    Numeric,
+    // Any MRZ character: document numbers and optional data
+    Any,
+}
+
+// Characters commonly confused by OCR engines
+const CONFUSIONS: &[(char, char)] = &[
+    ('O', '0'),
+    ('I', '1'),
+    ('B', '8'),
+    ('S', '5'),
+    ('Z', '2'),
+    ('G', '6'),
+    ('K', '<'),
+];
+
+// Character class of every field. Ranges are on the concatenated MRZ.
+fn field_classes(format: Format) -> Vec<(Range<usize>, Class)> {
+    use Class::*;
+
+    match format {
+        Format::Td1 => vec![
+            (0..5, Alpha),
+            (5..14, Any),
+            (14..30, Any),
+            (30..37, Numeric),
+            (37..38, Alpha),
+            (38..45, Numeric),
+            (45..48, Alpha),
+            (48..59, Any),
+            (59..60, Numeric),
+            (60..90, Alpha),
+        ],
+        Format::Td2 | Format::MrvB => vec![
+            (0..36, Alpha),
+            (36..45, Any),
+            (45..46, Any),
+            (46..49, Alpha),
+            (49..56, Numeric),
+            (56..57, Alpha),
+            (57..64, Numeric),
+            (64..71, Any),
+            (71..72, if format == Format::Td2 { Numeric } else { Any }),
+        ],
+        Format::Td3 | Format::MrvA => vec![
+            (0..44, Alpha),
+            (44..53, Any),
+            (53..54, Numeric),
+            (54..57, Alpha),
+            (57..64, Numeric),
+            (64..65, Alpha),
+            (65..72, Numeric),
+            (72..86, Any),
+            (86..88, if format == Format::Td3 { Numeric } else { Any }),
+        ],
+    }
+}
+
+// Fields verified by their own check digit (independently of the composite one),
+// as (field, check digit position) pairs.
+fn check_fields(format: Format) -> Vec<(Range<usize>, usize)> {
+    match format {
+        Format::Td1 => vec![(5..14, 14), (30..36, 36), (38..44, 44)],
+        Format::Td2 | Format::MrvB => vec![(36..45, 45), (49..55, 55), (57..63, 63)],
+        Format::Td3 | Format::MrvA => vec![(44..53, 53), (57..63, 63), (65..71, 71)],
+    }
+}
+
+fn alternatives(c: char) -> impl Iterator<Item = char> {
+    CONFUSIONS.iter().filter_map(move |&(a, b)| {
+        if a == c {
+            Some(b)
+        } else if b == c {
+            Some(a)
+        } else {
+            None
+        }
+    })
+}
+
+fn fits(c: char, class: Class) -> bool {
+    match class {
+        Class::Alpha => c.is_ascii_uppercase() || c == '<',
+        Class::Numeric => c.is_ascii_digit() || c == '<',
+        Class::Any => true,
+    }
+}
+
+fn check_digit_matches(mrz: &[char], field: &Range<usize>, check_index: usize) -> bool {
+    let field = mrz[field.clone()].iter().collect::<String>();
+    match mrz[check_index].to_digit(10) {
+        Some(digit) => verify_check_digit(&field, digit).is_ok(),
+        None => false,
+    }
+}
+
+/// Parse a Machine-readable Zone (MRZ), correcting characters commonly confused by
+/// OCR (O/0, I/1, B/8, S/5, Z/2, G/6 and </K). Characters that cannot appear in a
+/// field (e.g. letters in a date) are replaced first, then fields failing check digit
+/// verification are repaired when exactly one substitution makes them verify.
+/// The corrected MRZ must pass all check digits.
+pub fn parse_with_correction(data: &str) -> Result<CorrectedDocument, Error> {
+    let original = input::normalize(data)?;
+    let format = parser::format_of(&original).ok_or(ErrorKind::InvalidFormat)?;
+    let fields = field_classes(format);
+    let checks = check_fields(format);
+
+    let mut mrz = original.chars().collect::<Vec<_>>();
+
+    for (range, class) in &fields {
+        for c in &mut mrz[range.clone()] {
+            if !fits(*c, *class) {
+                if let Some(alternative) = alternatives(*c).find(|&a| fits(a, *class)) {
+                    *c = alternative;
+                }
+            }
+        }
+    }
+
+    for (field, check_index) in &checks {
+        if check_digit_matches(&mrz, field, *check_index) {
+            continue;
+        }
+
+        let class_at = |position: usize| {
+            fields
+                .iter()
+                .find(|(range, _)| range.contains(&position))
+                .map(|(_, class)| *class)
+                .unwrap_or(Class::Any)
+        };
+
+        let mut solutions = Vec::new();
+        for position in field.clone().chain(Some(*check_index)) {
+            let current = mrz[position];
+            for alternative in alternatives(current).filter(|&a| fits(a, class_at(position))) {
+                mrz[position] = alternative;
+                if check_digit_matches(&mrz, field, *check_index) {
+                    solutions.push((position, alternative));
+                }
+                mrz[position] = current;
+            }
+        }
+
+        if let [(position, alternative)] = solutions[..] {
+            mrz[position] = alternative;
+        }
+    }
+
+    let mrz = mrz.into_iter().collect::<String>();
+    let document = parser::parse(&mrz, true)?;
+
+    let corrections = original
+        .chars()
+        .zip(mrz.chars())
+        .enumerate()
+        .filter(|(_, (from, to))| from != to)
+        .map(|(position, (from, to))| Correction { position, from, to })
+        .collect();
+
+    Ok(CorrectedDocument {
+        document,
+        mrz,
+        corrections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn parse_with_correction_valid() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let corrected = parse_with_correction(mrz).unwrap();
+        assert_eq!(corrected.mrz, mrz);
+        assert!(corrected.corrections.is_empty());
+    }
+
+    #[test]
+    fn parse_with_correction_field_classes() {
+        let mrz = "P<UT0ERIK55ON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO74O8I22F12O4I59ZE184226B<<<<<IO";
+        let corrected = parse_with_correction(mrz).unwrap();
+        match corrected.document {
+            Document::Passport(passport) => {
+                assert_eq!(passport.country, "UTO");
+                assert_eq!(passport.surnames, vec!["ERIKSSON"]);
+                assert_eq!(passport.birth_date.month(), 8);
+            }
+            _ => panic!("unexpected document"),
+        }
+        assert_eq!(
+            corrected.corrections[0],
+            Correction {
+                position: 4,
+                from: '0',
+                to: 'O'
+            }
+        );
+        assert_eq!(corrected.corrections.len(), 9);
+    }
+
+    #[test]
+    fn parse_with_correction_check_digit() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L8989O2C36UTO7408122F1204159ZE184226B<<<<<10";
+        let corrected = parse_with_correction(mrz).unwrap();
+        assert_eq!(
+            corrected.corrections,
+            vec![Correction {
+                position: 49,
+                from: 'O',
+                to: '0'
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_with_correction_uncorrectable() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C37UTO7408122F1204159ZE184226B<<<<<10";
+        assert_eq!(
+            parse_with_correction(mrz).unwrap_err().kind(),
+            ErrorKind::BadCheckDigit
+        );
+    }
+}
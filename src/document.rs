@@ -1,6 +1,12 @@
-use chrono::NaiveDate;
+use crate::country::CountryCode;
+use crate::error::{Error, ErrorKind};
+use crate::raw::RawMrz;
+use chrono::{Datelike, Duration, NaiveDate};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::{self, FromStr};
 
 /// Travel document
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -14,6 +20,322 @@ pub enum Document {
     Passport(Passport),
     /// Identity Card
     IdentityCard(IdentityCard),
+    /// Visa
+    Visa(Visa),
+}
+
+impl Document {
+    /// Format of the MRZ the document was parsed from
+    pub fn format(&self) -> Format {
+        match self {
+            Document::Passport(_) => Format::Td3,
+            Document::IdentityCard(identity_card) => identity_card.format,
+            Document::Visa(visa) => visa.format,
+        }
+    }
+
+    /// Document code
+    pub fn document_code(&self) -> DocumentCode {
+        match self {
+            Document::Passport(passport) => passport.document_code,
+            Document::IdentityCard(identity_card) => identity_card.document_code,
+            Document::Visa(visa) => visa.document_code,
+        }
+    }
+
+    /// Document number (passport, identity card or visa number)
+    pub fn document_number(&self) -> &str {
+        match self {
+            Document::Passport(passport) => &passport.passport_number,
+            Document::IdentityCard(identity_card) => &identity_card.document_number,
+            Document::Visa(visa) => &visa.visa_number,
+        }
+    }
+
+    /// Issuing state or organization, if listed in the ICAO registry
+    pub fn country_code(&self) -> Option<CountryCode> {
+        match self {
+            Document::Passport(passport) => CountryCode::lookup(&passport.country),
+            Document::IdentityCard(identity_card) => CountryCode::lookup(&identity_card.country),
+            Document::Visa(visa) => CountryCode::lookup(&visa.country),
+        }
+    }
+
+    /// Nationality of the holder, if listed in the ICAO registry
+    pub fn nationality_code(&self) -> Option<CountryCode> {
+        match self {
+            Document::Passport(passport) => CountryCode::lookup(&passport.nationality),
+            Document::IdentityCard(identity_card) => {
+                CountryCode::lookup(&identity_card.nationality)
+            }
+            Document::Visa(visa) => CountryCode::lookup(&visa.nationality),
+        }
+    }
+
+    /// MRZ as read, if kept while parsing (see [`ParseOptions::keep_raw`](crate::ParseOptions::keep_raw))
+    pub fn raw(&self) -> Option<&RawMrz> {
+        match self {
+            Document::Passport(passport) => passport.raw.as_ref(),
+            Document::IdentityCard(identity_card) => identity_card.raw.as_ref(),
+            Document::Visa(visa) => visa.raw.as_ref(),
+        }
+    }
+
+    /// Date of birth of the holder (see [`Passport::birth_date`])
+    pub fn birth_date(&self) -> NaiveDate {
+        match self {
+            Document::Passport(passport) => passport.birth_date,
+            Document::IdentityCard(identity_card) => identity_card.birth_date,
+            Document::Visa(visa) => visa.birth_date,
+        }
+    }
+
+    /// Date of birth of the holder as it appears in the MRZ, where the month and day may be unknown
+    pub fn birth_date_partial(&self) -> PartialDate {
+        match self {
+            Document::Passport(passport) => passport.birth_date_partial,
+            Document::IdentityCard(identity_card) => identity_card.birth_date_partial,
+            Document::Visa(visa) => visa.birth_date_partial,
+        }
+    }
+
+    /// Date of expiry of the document
+    pub fn expiry_date(&self) -> NaiveDate {
+        match self {
+            Document::Passport(passport) => passport.expiry_date,
+            Document::IdentityCard(identity_card) => identity_card.expiry_date,
+            Document::Visa(visa) => visa.expiry_date,
+        }
+    }
+}
+
+impl Validity for Document {
+    fn expiry_date(&self) -> NaiveDate {
+        Document::expiry_date(self)
+    }
+
+    fn birth_date(&self) -> NaiveDate {
+        Document::birth_date(self)
+    }
+
+    fn birth_date_partial(&self) -> PartialDate {
+        Document::birth_date_partial(self)
+    }
+}
+
+/// Checks on the date of expiry of a document and the age of its holder
+pub trait Validity {
+    /// Date of expiry of the document
+    fn expiry_date(&self) -> NaiveDate;
+
+    /// Date of birth of the holder, the latest date allowed by [`Validity::birth_date_partial`]
+    fn birth_date(&self) -> NaiveDate;
+
+    /// Date of birth of the holder as it appears in the MRZ
+    fn birth_date_partial(&self) -> PartialDate;
+
+    /// Whether the document has expired at `date` (it is still valid on its date of expiry)
+    fn is_expired(&self, date: NaiveDate) -> bool {
+        self.is_expired_with_grace(date, Duration::zero())
+    }
+
+    /// Whether the document has expired at `date`, allowing for a grace period after the date of expiry
+    fn is_expired_with_grace(&self, date: NaiveDate, grace: Duration) -> bool {
+        match self.expiry_date().checked_add_signed(grace) {
+            Some(last_valid_date) => date > last_valid_date,
+            None => grace < Duration::zero(),
+        }
+    }
+
+    /// Days from `date` until the date of expiry (negative once expired)
+    fn remaining_days(&self, date: NaiveDate) -> i64 {
+        self.expiry_date().signed_duration_since(date).num_days()
+    }
+
+    /// Age of the holder at `date` (see [`PartialDate::age_at`]), or `None` if the date of
+    /// birth disagrees with its partial form
+    fn age_at(&self, date: NaiveDate) -> Option<u32> {
+        let birth_date = self.birth_date_partial();
+        if birth_date.latest_date() != Some(self.birth_date()) {
+            return None;
+        }
+
+        birth_date.age_at(date)
+    }
+}
+
+/// Size and layout of a machine-readable zone
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Format {
+    /// TD1: 3 lines of 30 characters
+    Td1,
+    /// TD2: 2 lines of 36 characters
+    Td2,
+    /// TD3: 2 lines of 44 characters
+    Td3,
+    /// MRV-A: 2 lines of 44 characters
+    MrvA,
+    /// MRV-B: 2 lines of 36 characters
+    MrvB,
+}
+
+impl Format {
+    /// Number of lines in the MRZ
+    pub fn lines(self) -> usize {
+        match self {
+            Format::Td1 => 3,
+            Format::Td2 | Format::Td3 | Format::MrvA | Format::MrvB => 2,
+        }
+    }
+
+    /// Number of characters in each line of the MRZ
+    pub fn line_length(self) -> usize {
+        match self {
+            Format::Td1 => 30,
+            Format::Td2 | Format::MrvB => 36,
+            Format::Td3 | Format::MrvA => 44,
+        }
+    }
+}
+
+/// Document code: the type of document (`P`, `I`, `A`, `C` or `V`) followed by a
+/// subtype at the discretion of the issuing state, or a filler
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(into = "String", try_from = "String")
+)]
+pub struct DocumentCode([u8; 2]);
+
+impl DocumentCode {
+    /// Full two-character code, including any filler (e.g. `P<` or `PD`)
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.0).unwrap()
+    }
+
+    /// Type of document (`P`, `I`, `A`, `C` or `V`)
+    pub fn document_type(&self) -> char {
+        char::from(self.0[0])
+    }
+
+    /// Subtype of the document, unless the second character is a filler
+    pub fn subtype(&self) -> Option<DocumentSubtype> {
+        let subtype = match (self.0[0], self.0[1]) {
+            (_, b'<') => return None,
+            (b'P', b'D') => DocumentSubtype::Diplomatic,
+            (b'P', b'S') => DocumentSubtype::Service,
+            (b'P', b'O') => DocumentSubtype::Official,
+            (b'P', b'E') => DocumentSubtype::Emergency,
+            (b'I', b'R') => DocumentSubtype::ResidencePermit,
+            (b'A', b'C') => DocumentSubtype::CrewMember,
+            (_, subtype) => DocumentSubtype::Other(char::from(subtype)),
+        };
+
+        Some(subtype)
+    }
+}
+
+impl FromStr for DocumentCode {
+    type Err = Error;
+
+    /// Parse a code of one or two characters (e.g. `P`, `P<` or `PD`)
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let valid_subtype = |c: u8| c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'<';
+
+        match *code.as_bytes() {
+            [document_type] if document_type.is_ascii_uppercase() => {
+                Ok(DocumentCode([document_type, b'<']))
+            }
+            [document_type, subtype]
+                if document_type.is_ascii_uppercase() && valid_subtype(subtype) =>
+            {
+                Ok(DocumentCode([document_type, subtype]))
+            }
+            _ => Err(ErrorKind::InvalidDocumentType.into()),
+        }
+    }
+}
+
+impl TryFrom<String> for DocumentCode {
+    type Error = Error;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        code.parse()
+    }
+}
+
+impl From<DocumentCode> for String {
+    fn from(code: DocumentCode) -> Self {
+        code.as_str().to_string()
+    }
+}
+
+impl fmt::Display for DocumentCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Known interpretations of the second character of the document code
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DocumentSubtype {
+    /// Diplomatic passport (`PD`)
+    Diplomatic,
+    /// Service passport (`PS`)
+    Service,
+    /// Official passport (`PO`)
+    Official,
+    /// Emergency passport (`PE`)
+    Emergency,
+    /// Residence permit (`IR`)
+    ResidencePermit,
+    /// Crew member certificate (`AC`)
+    CrewMember,
+    /// Other subtype defined by the issuing state
+    Other(char),
+}
+
+/// MRZ field
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Field {
+    /// Document code
+    DocumentCode,
+    /// Issuing state or organization
+    IssuingState,
+    /// Name of the holder
+    Name,
+    /// Nationality
+    Nationality,
+    /// Sex
+    Sex,
+    /// Optional data elements
+    OptionalData,
+    /// Document number
+    DocumentNumber,
+    /// Date of birth
+    BirthDate,
+    /// Date of expiry
+    ExpiryDate,
+    /// Personal number or other optional data elements
+    PersonalNumber,
+    /// Composite of the document number, dates and optional data
+    Composite,
 }
 
 /// Gender
@@ -28,14 +350,78 @@ pub enum Gender {
     Male,
     /// Female
     Female,
-    /// Other/unspecified
+    /// Unspecified (`X` or `<`)
+    Unspecified,
+    /// Unrecognized character, only when parsing without checks
     Other,
 }
 
+/// Date in which the month and day may be unknown (filled with `<` in the MRZ),
+/// as allowed for the date of birth
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialDate {
+    /// Year
+    pub year: i32,
+    /// Month (1 to 12), if known
+    pub month: Option<u32>,
+    /// Day of the month, if known (only when the month is known)
+    pub day: Option<u32>,
+}
+
+impl PartialDate {
+    /// Whether both the month and day are known
+    pub fn is_complete(&self) -> bool {
+        self.month.is_some() && self.day.is_some()
+    }
+
+    /// Full date, if both the month and day are known
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year, self.month?, self.day?)
+    }
+
+    /// Latest full date this could be, taking an unknown month as December and an
+    /// unknown day as the last day of the month
+    pub fn latest_date(&self) -> Option<NaiveDate> {
+        let month = self.month.unwrap_or(12);
+
+        match self.day {
+            Some(day) => NaiveDate::from_ymd_opt(self.year, month, day),
+            None if month == 12 => NaiveDate::from_ymd_opt(self.year, 12, 31),
+            None => NaiveDate::from_ymd_opt(self.year, month + 1, 1)?.pred_opt(),
+        }
+    }
+
+    /// Age at `date` of someone born on this date, or `None` if `date` is before it.
+    /// An unknown month or day is taken as the latest possible (see [`PartialDate::latest_date`]),
+    /// so the age is never overstated.
+    /// Someone born on February 29 turns a year older on March 1 in common years.
+    pub fn age_at(&self, date: NaiveDate) -> Option<u32> {
+        let birth_date = self.latest_date()?;
+        let birthday = (birth_date.month(), birth_date.day());
+        let age =
+            date.year() - birth_date.year() - i32::from((date.month(), date.day()) < birthday);
+
+        u32::try_from(age).ok()
+    }
+}
+
+impl From<NaiveDate> for PartialDate {
+    fn from(date: NaiveDate) -> Self {
+        PartialDate {
+            year: date.year(),
+            month: Some(date.month()),
+            day: Some(date.day()),
+        }
+    }
+}
+
 /// Passport
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Passport {
+    /// Document code (e.g. `PD` for a diplomatic passport)
+    pub document_code: DocumentCode,
     /// Country (ISO 3166-1 code)
     pub country: String,
     /// Surname
@@ -46,18 +432,81 @@ pub struct Passport {
     pub passport_number: String,
     /// Nationality (ISO 3166-1 code)
     pub nationality: String,
-    /// Date of birth
+    /// Date of birth, with an unknown month or day taken as the latest possible
+    /// (see [`PartialDate::latest_date`]), which must hold to encode the document
     pub birth_date: NaiveDate,
+    /// Date of birth as it appears in the MRZ, where the month and day may be unknown
+    pub birth_date_partial: PartialDate,
     /// Gender
     pub gender: Gender,
+    /// Sex as it appears in the MRZ
+    pub sex: char,
     /// Date of expiry
     pub expiry_date: NaiveDate,
+    /// Personal number or other optional data elements
+    pub personal_number: String,
+    /// MRZ as read, if kept while parsing (not compared for equality)
+    pub raw: Option<RawMrz>,
+}
+
+// Compares the decoded fields only, whether or not the MRZ was kept as read
+impl PartialEq for Passport {
+    fn eq(&self, other: &Self) -> bool {
+        let Passport {
+            document_code,
+            country,
+            surnames,
+            given_names,
+            passport_number,
+            nationality,
+            birth_date,
+            birth_date_partial,
+            gender,
+            sex,
+            expiry_date,
+            personal_number,
+            raw: _,
+        } = self;
+
+        *document_code == other.document_code
+            && *country == other.country
+            && *surnames == other.surnames
+            && *given_names == other.given_names
+            && *passport_number == other.passport_number
+            && *nationality == other.nationality
+            && *birth_date == other.birth_date
+            && *birth_date_partial == other.birth_date_partial
+            && *gender == other.gender
+            && *sex == other.sex
+            && *expiry_date == other.expiry_date
+            && *personal_number == other.personal_number
+    }
+}
+
+impl Eq for Passport {}
+
+impl Validity for Passport {
+    fn expiry_date(&self) -> NaiveDate {
+        self.expiry_date
+    }
+
+    fn birth_date(&self) -> NaiveDate {
+        self.birth_date
+    }
+
+    fn birth_date_partial(&self) -> PartialDate {
+        self.birth_date_partial
+    }
 }
 
 /// Identity Card
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IdentityCard {
+    /// Document code (e.g. `IR` for a residence permit)
+    pub document_code: DocumentCode,
+    /// MRZ format (TD1 or TD2)
+    pub format: Format,
     /// Country (ISO 3166-1 code)
     pub country: String,
     /// Surname
@@ -68,10 +517,317 @@ pub struct IdentityCard {
     pub document_number: String,
     /// Nationality (ISO 3166-1 code)
     pub nationality: String,
-    /// Date of birth
+    /// Date of birth, with an unknown month or day taken as the latest possible
+    /// (see [`PartialDate::latest_date`]), which must hold to encode the document
     pub birth_date: NaiveDate,
+    /// Date of birth as it appears in the MRZ, where the month and day may be unknown
+    pub birth_date_partial: PartialDate,
     /// Gender
     pub gender: Gender,
+    /// Sex as it appears in the MRZ
+    pub sex: char,
     /// Date of expiry
     pub expiry_date: NaiveDate,
+    /// Optional data elements (line 1 for TD1, line 2 for TD2)
+    pub optional_data: String,
+    /// Optional data elements on line 2 (TD1 only)
+    pub optional_data_2: String,
+    /// MRZ as read, if kept while parsing (not compared for equality)
+    pub raw: Option<RawMrz>,
+}
+
+// Compares the decoded fields only, whether or not the MRZ was kept as read
+impl PartialEq for IdentityCard {
+    fn eq(&self, other: &Self) -> bool {
+        let IdentityCard {
+            document_code,
+            format,
+            country,
+            surnames,
+            given_names,
+            document_number,
+            nationality,
+            birth_date,
+            birth_date_partial,
+            gender,
+            sex,
+            expiry_date,
+            optional_data,
+            optional_data_2,
+            raw: _,
+        } = self;
+
+        *document_code == other.document_code
+            && *format == other.format
+            && *country == other.country
+            && *surnames == other.surnames
+            && *given_names == other.given_names
+            && *document_number == other.document_number
+            && *nationality == other.nationality
+            && *birth_date == other.birth_date
+            && *birth_date_partial == other.birth_date_partial
+            && *gender == other.gender
+            && *sex == other.sex
+            && *expiry_date == other.expiry_date
+            && *optional_data == other.optional_data
+            && *optional_data_2 == other.optional_data_2
+    }
+}
+
+impl Eq for IdentityCard {}
+
+impl Validity for IdentityCard {
+    fn expiry_date(&self) -> NaiveDate {
+        self.expiry_date
+    }
+
+    fn birth_date(&self) -> NaiveDate {
+        self.birth_date
+    }
+
+    fn birth_date_partial(&self) -> PartialDate {
+        self.birth_date_partial
+    }
+}
+
+/// Visa
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Visa {
+    /// Document code (e.g. `VA`, where the second letter is at the discretion of the issuing state)
+    pub document_code: DocumentCode,
+    /// MRZ format (MRV-A or MRV-B)
+    pub format: Format,
+    /// Issuing state (ISO 3166-1 code)
+    pub country: String,
+    /// Surname
+    pub surnames: Vec<String>,
+    /// Given names
+    pub given_names: Vec<String>,
+    /// Visa number
+    pub visa_number: String,
+    /// Nationality (ISO 3166-1 code)
+    pub nationality: String,
+    /// Date of birth, with an unknown month or day taken as the latest possible
+    /// (see [`PartialDate::latest_date`]), which must hold to encode the document
+    pub birth_date: NaiveDate,
+    /// Date of birth as it appears in the MRZ, where the month and day may be unknown
+    pub birth_date_partial: PartialDate,
+    /// Gender
+    pub gender: Gender,
+    /// Sex as it appears in the MRZ
+    pub sex: char,
+    /// Date of expiry
+    pub expiry_date: NaiveDate,
+    /// Optional data at the discretion of the issuing state
+    pub optional_data: String,
+    /// MRZ as read, if kept while parsing (not compared for equality)
+    pub raw: Option<RawMrz>,
+}
+
+// Compares the decoded fields only, whether or not the MRZ was kept as read
+impl PartialEq for Visa {
+    fn eq(&self, other: &Self) -> bool {
+        let Visa {
+            document_code,
+            format,
+            country,
+            surnames,
+            given_names,
+            visa_number,
+            nationality,
+            birth_date,
+            birth_date_partial,
+            gender,
+            sex,
+            expiry_date,
+            optional_data,
+            raw: _,
+        } = self;
+
+        *document_code == other.document_code
+            && *format == other.format
+            && *country == other.country
+            && *surnames == other.surnames
+            && *given_names == other.given_names
+            && *visa_number == other.visa_number
+            && *nationality == other.nationality
+            && *birth_date == other.birth_date
+            && *birth_date_partial == other.birth_date_partial
+            && *gender == other.gender
+            && *sex == other.sex
+            && *expiry_date == other.expiry_date
+            && *optional_data == other.optional_data
+    }
+}
+
+impl Eq for Visa {}
+
+impl Validity for Visa {
+    fn expiry_date(&self) -> NaiveDate {
+        self.expiry_date
+    }
+
+    fn birth_date(&self) -> NaiveDate {
+        self.birth_date
+    }
+
+    fn birth_date_partial(&self) -> PartialDate {
+        self.birth_date_partial
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn expiry() {
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(document.expiry_date(), date(2012, 4, 15));
+        assert!(!document.is_expired(date(2012, 4, 15)));
+        assert!(document.is_expired(date(2012, 4, 16)));
+        assert!(!document.is_expired_with_grace(date(2012, 4, 16), Duration::days(1)));
+        assert!(document.is_expired_with_grace(date(2012, 4, 17), Duration::days(1)));
+        assert!(document.is_expired_with_grace(date(2012, 4, 15), Duration::days(-1)));
+        assert!(!document.is_expired_with_grace(date(2012, 4, 15), Duration::days(365_000_000)));
+        assert_eq!(document.remaining_days(date(2012, 4, 5)), 10);
+        assert_eq!(document.remaining_days(date(2012, 4, 15)), 0);
+        assert_eq!(document.remaining_days(date(2013, 4, 15)), -365);
+
+        match document {
+            Document::Passport(passport) => {
+                assert!(passport.is_expired(date(2012, 4, 16)));
+                assert_eq!(passport.remaining_days(date(2012, 4, 5)), 10);
+                assert_eq!(passport.age_at(date(2012, 4, 5)), Some(37));
+
+                let mut passport = passport.clone();
+                passport.birth_date = date(1975, 8, 12);
+                assert_eq!(passport.age_at(date(2012, 4, 5)), None);
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn country_codes() {
+        let document = parse(
+            "I<UTOD23145890<7349<ABC123<<<<\
+             3407127M9507122XXA<<<<<<<<<<<3\
+             STEVENSON<<PETER<JOHN<<<<<<<<<",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(document.country_code().unwrap().name(), "Utopia (specimen)");
+        assert_eq!(document.nationality_code().unwrap().code(), "XXA");
+
+        let document = parse(
+            "P<QQQERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(document.country_code(), None);
+        assert_eq!(document.nationality_code().unwrap().code(), "UTO");
+    }
+
+    #[test]
+    fn document_code() {
+        let code = "PO".parse::<DocumentCode>().unwrap();
+        assert_eq!(code.document_type(), 'P');
+        assert_eq!(code.subtype(), Some(DocumentSubtype::Official));
+        assert_eq!(code.to_string(), "PO");
+
+        let code = "V".parse::<DocumentCode>().unwrap();
+        assert_eq!(code.as_str(), "V<");
+        assert_eq!(code.subtype(), None);
+
+        let code = "PM".parse::<DocumentCode>().unwrap();
+        assert_eq!(code.subtype(), Some(DocumentSubtype::Other('M')));
+
+        for code in ["", "<", "1P", "P<<", "p"] {
+            assert_eq!(
+                code.parse::<DocumentCode>().unwrap_err().kind(),
+                ErrorKind::InvalidDocumentType
+            );
+        }
+    }
+
+    #[test]
+    fn age() {
+        let birth_date = PartialDate::from(date(2006, 10, 16));
+        assert_eq!(birth_date.age_at(date(2024, 10, 15)), Some(17));
+        assert_eq!(birth_date.age_at(date(2024, 10, 16)), Some(18));
+        assert_eq!(birth_date.age_at(date(2006, 10, 16)), Some(0));
+        assert_eq!(birth_date.age_at(date(2006, 10, 15)), None);
+    }
+
+    #[test]
+    fn age_leap_day() {
+        let birth_date = PartialDate::from(date(2004, 2, 29));
+        assert_eq!(birth_date.age_at(date(2022, 2, 28)), Some(17));
+        assert_eq!(birth_date.age_at(date(2022, 3, 1)), Some(18));
+        assert_eq!(birth_date.age_at(date(2024, 2, 28)), Some(19));
+        assert_eq!(birth_date.age_at(date(2024, 2, 29)), Some(20));
+    }
+
+    #[test]
+    fn age_partial_date() {
+        let birth_date = PartialDate {
+            year: 2006,
+            month: Some(10),
+            day: None,
+        };
+        assert_eq!(birth_date.age_at(date(2024, 10, 30)), Some(17));
+        assert_eq!(birth_date.age_at(date(2024, 10, 31)), Some(18));
+
+        let birth_date = PartialDate {
+            year: 2006,
+            month: None,
+            day: None,
+        };
+        assert_eq!(birth_date.age_at(date(2024, 12, 30)), Some(17));
+        assert_eq!(birth_date.age_at(date(2024, 12, 31)), Some(18));
+        assert_eq!(birth_date.age_at(date(2006, 6, 1)), None);
+    }
+
+    #[test]
+    fn age_partial_date_february() {
+        let birth_date = PartialDate {
+            year: 2005,
+            month: Some(2),
+            day: None,
+        };
+        assert_eq!(birth_date.latest_date(), Some(date(2005, 2, 28)));
+        assert_eq!(birth_date.age_at(date(2023, 2, 27)), Some(17));
+        assert_eq!(birth_date.age_at(date(2023, 2, 28)), Some(18));
+        assert_eq!(birth_date.age_at(date(2024, 2, 28)), Some(19));
+    }
+
+    #[test]
+    fn age_partial_date_leap_year() {
+        let birth_date = PartialDate {
+            year: 2004,
+            month: Some(2),
+            day: None,
+        };
+        assert_eq!(birth_date.latest_date(), Some(date(2004, 2, 29)));
+        assert_eq!(birth_date.age_at(date(2022, 2, 28)), Some(17));
+        assert_eq!(birth_date.age_at(date(2022, 3, 1)), Some(18));
+        assert_eq!(birth_date.age_at(date(2024, 2, 28)), Some(19));
+        assert_eq!(birth_date.age_at(date(2024, 2, 29)), Some(20));
+    }
 }
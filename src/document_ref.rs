@@ -0,0 +1,163 @@
+use crate::document::*;
+use chrono::NaiveDate;
+use std::borrow::Cow;
+
+/// Travel document borrowing its fields from the MRZ it was parsed from, avoiding
+/// allocations when processing MRZs in bulk.
+/// Fields are given without fillers and names are split on demand.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DocumentRef<'a> {
+    /// MRZ format
+    pub format: Format,
+    /// Document code
+    pub document_code: DocumentCode,
+    /// Issuing state or organization
+    pub country: Cow<'a, str>,
+    /// Name field, with the primary and secondary identifiers separated by `<<`
+    pub names: &'a str,
+    /// Document number (passport, identity card or visa number)
+    pub document_number: Cow<'a, str>,
+    /// Nationality
+    pub nationality: Cow<'a, str>,
+    /// Date of birth, with an unknown month or day taken as the latest possible
+    /// (see [`PartialDate::latest_date`]), which must hold to encode the document
+    pub birth_date: NaiveDate,
+    /// Date of birth as it appears in the MRZ, where the month and day may be unknown
+    pub birth_date_partial: PartialDate,
+    /// Gender
+    pub gender: Gender,
+    /// Sex as it appears in the MRZ
+    pub sex: char,
+    /// Date of expiry
+    pub expiry_date: NaiveDate,
+    /// Optional data (personal number for passports, line 1 for TD1 identity cards)
+    pub optional_data: &'a str,
+    /// Optional data on line 2 (TD1 only)
+    pub optional_data_2: &'a str,
+}
+
+impl<'a> DocumentRef<'a> {
+    /// Surnames (primary identifier)
+    pub fn surnames(&self) -> impl Iterator<Item = &'a str> {
+        surnames(self.names)
+    }
+
+    /// Given names (secondary identifier)
+    pub fn given_names(&self) -> impl Iterator<Item = &'a str> {
+        given_names(self.names)
+    }
+
+    /// Copy the fields into an owned [`Document`]
+    pub fn to_document(&self) -> Document {
+        let surnames = self.surnames().map(String::from).collect();
+        let given_names = self.given_names().map(String::from).collect();
+
+        match self.format {
+            Format::Td3 => Document::Passport(Passport {
+                document_code: self.document_code,
+                country: self.country.to_string(),
+                surnames,
+                given_names,
+                passport_number: self.document_number.to_string(),
+                nationality: self.nationality.to_string(),
+                birth_date: self.birth_date,
+                birth_date_partial: self.birth_date_partial,
+                gender: self.gender.clone(),
+                sex: self.sex,
+                expiry_date: self.expiry_date,
+                personal_number: self.optional_data.to_string(),
+                raw: None,
+            }),
+            Format::Td1 | Format::Td2 => Document::IdentityCard(IdentityCard {
+                document_code: self.document_code,
+                format: self.format,
+                country: self.country.to_string(),
+                surnames,
+                given_names,
+                document_number: self.document_number.to_string(),
+                nationality: self.nationality.to_string(),
+                birth_date: self.birth_date,
+                birth_date_partial: self.birth_date_partial,
+                gender: self.gender.clone(),
+                sex: self.sex,
+                expiry_date: self.expiry_date,
+                optional_data: self.optional_data.to_string(),
+                optional_data_2: self.optional_data_2.to_string(),
+                raw: None,
+            }),
+            Format::MrvA | Format::MrvB => Document::Visa(Visa {
+                document_code: self.document_code,
+                format: self.format,
+                country: self.country.to_string(),
+                surnames,
+                given_names,
+                visa_number: self.document_number.to_string(),
+                nationality: self.nationality.to_string(),
+                birth_date: self.birth_date,
+                birth_date_partial: self.birth_date_partial,
+                gender: self.gender.clone(),
+                sex: self.sex,
+                expiry_date: self.expiry_date,
+                optional_data: self.optional_data.to_string(),
+                raw: None,
+            }),
+        }
+    }
+}
+
+// Surnames and given names of a name field, separated by `<<`
+pub(crate) fn surnames(names: &str) -> impl Iterator<Item = &str> {
+    split_names(names.split("<<").next())
+}
+
+pub(crate) fn given_names(names: &str) -> impl Iterator<Item = &str> {
+    split_names(names.split("<<").nth(1))
+}
+
+fn split_names(names: Option<&str>) -> impl Iterator<Item = &str> {
+    names
+        .unwrap_or_default()
+        .split('<')
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, parse_ref};
+    use std::borrow::Cow;
+
+    #[test]
+    fn borrowed_fields() {
+        let mrz = "I<UTOD23145890<7349<ABC123<<<<\
+                   3407127M9507122UTO<<<<<<<<<<<3\
+                   STEVENSON<<PETER<JOHN<<<<<<<<<";
+        let document = parse_ref(mrz, true).unwrap();
+
+        assert_eq!(document.country, "UTO");
+        assert!(matches!(document.country, Cow::Borrowed(_)));
+        assert_eq!(document.surnames().collect::<Vec<_>>(), vec!["STEVENSON"]);
+        assert_eq!(
+            document.given_names().collect::<Vec<_>>(),
+            vec!["PETER", "JOHN"]
+        );
+        assert_eq!(document.document_number, "D23145890734");
+        assert!(matches!(document.document_number, Cow::Owned(_)));
+        assert_eq!(document.optional_data, "ABC123");
+        assert_eq!(document.to_document(), parse(mrz, true).unwrap());
+    }
+
+    #[test]
+    fn to_document() {
+        for mrz in [
+            "P<CANMARTIN<<SARAH<<<<<<<<<<<<<<<<<<<<<<<<<<\
+             ZE000509<9CAN8501019F2301147<<<<<<<<<<<<<<<8",
+            "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+             D231458907UTO7408122F1204159<<<<<<<6",
+            "V<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L8988901C4XXX4009078F96121096ZE184226B<<<<<<",
+        ] {
+            let document = parse_ref(mrz, true).unwrap();
+            assert_eq!(document.to_document(), parse(mrz, true).unwrap());
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use crate::check_digit::compute_check_digit;
+use crate::document::*;
+use crate::error::{Error, ErrorKind};
+use chrono::NaiveDate;
+
+const DATE_FORMAT: &str = "%y%m%d";
+
+/// Encode a travel document into its Machine-readable Zone (MRZ), with the lines
+/// concatenated in the same form accepted by [`parse`](crate::parse).
+/// Names that do not fit are truncated and all check digits are computed.
+/// The date of birth must be the latest date allowed by the partial date of birth.
+pub fn encode(document: &Document) -> Result<String, Error> {
+    match document {
+        Document::Passport(passport) => encode_passport(passport),
+        Document::IdentityCard(identity_card) => match identity_card.format {
+            Format::Td1 => encode_identity_card(identity_card),
+            Format::Td2 => encode_td2_identity_card(identity_card),
+            _ => Err(ErrorKind::InvalidFormat.into()),
+        },
+        Document::Visa(visa) => encode_visa(visa),
+    }
+}
+
+fn validate(value: &str) -> Result<(), Error> {
+    match value.find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '<')) {
+        Some(offset) => Err(Error::at(ErrorKind::InvalidChar, None, value, offset)),
+        None => Ok(()),
+    }
+}
+
+// Pads a field with fillers, rejecting values which do not fit or contain characters
+// not allowed in an MRZ.
+pub(crate) fn field(value: &str, length: usize) -> Result<String, Error> {
+    validate(value)?;
+
+    if value.len() > length {
+        return Err(ErrorKind::InvalidFormat.into());
+    }
+
+    Ok(format!("{:<<width$}", value, width = length))
+}
+
+fn document_code(code: &DocumentCode, allowed: &[char]) -> Result<String, Error> {
+    if allowed.contains(&code.document_type()) {
+        Ok(code.to_string())
+    } else {
+        Err(ErrorKind::InvalidDocumentType.into())
+    }
+}
+
+pub(crate) fn check_digit(slice: &str) -> Result<char, Error> {
+    Ok(char::from(b'0' + compute_check_digit(slice)?))
+}
+
+pub(crate) fn date(date: &NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+// Date of birth as written from its partial form, with an unknown month and day
+// written as fillers. Both forms must agree, so that neither is silently ignored.
+pub(crate) fn birth_date(birth_date: NaiveDate, date: &PartialDate) -> Result<String, Error> {
+    let component = |value: Option<u32>| match value {
+        Some(value) => format!("{:02}", value),
+        None => "<<".to_string(),
+    };
+
+    if date.month.is_none() && date.day.is_some() || date.latest_date() != Some(birth_date) {
+        return Err(ErrorKind::InvalidBirthDate.into());
+    }
+
+    Ok(format!(
+        "{:02}{}{}",
+        date.year.rem_euclid(100),
+        component(date.month),
+        component(date.day)
+    ))
+}
+
+// Unspecified sex keeps its original representation (`X` or `<`)
+fn gender(gender: &Gender, sex: char) -> char {
+    match gender {
+        Gender::Male => 'M',
+        Gender::Female => 'F',
+        Gender::Unspecified | Gender::Other if sex == 'X' => 'X',
+        Gender::Unspecified | Gender::Other => '<',
+    }
+}
+
+fn name_components(names: &[String]) -> Result<String, Error> {
+    let names = names
+        .iter()
+        .map(|name| name.replace([' ', '-'], "<"))
+        .collect::<Vec<_>>();
+    let names = names.join("<");
+    validate(&names)?;
+    Ok(names)
+}
+
+// Name truncation from https://www.icao.int/publications/Documents/9303_p3_cons_en.pdf (section 4.6):
+// the primary identifier (surnames) is preserved and the secondary identifier (given
+// names) truncated first. A truncated name fills the whole field, ending in a letter.
+fn names(surnames: &[String], given_names: &[String], length: usize) -> Result<String, Error> {
+    let primary = name_components(surnames)?;
+    let secondary = name_components(given_names)?;
+
+    let full = if secondary.is_empty() {
+        primary.clone()
+    } else {
+        format!("{}<<{}", primary, secondary)
+    };
+
+    if full.len() <= length {
+        return field(&full, length);
+    }
+
+    let truncated = if primary.len() + 3 <= length {
+        full[..length].trim_end_matches('<')
+    } else {
+        primary[..primary.len().min(length)].trim_end_matches('<')
+    };
+
+    field(truncated, length)
+}
+
+// Document numbers longer than nine characters continue in the optional data field,
+// followed by their check digit and a filler. The check digit position after the
+// first nine characters then holds a filler.
+fn document_number(
+    number: &str,
+    optional_data: &str,
+    length: usize,
+) -> Result<(String, String), Error> {
+    validate(number)?;
+
+    if number.len() <= 9 {
+        let number = field(number, 9)?;
+        let number = format!("{}{}", number, check_digit(&number)?);
+        return Ok((number, field(optional_data, length)?));
+    }
+
+    let overflow = format!("{}{}<{}", &number[9..], check_digit(number)?, optional_data);
+
+    Ok((format!("{}<", &number[..9]), field(&overflow, length)?))
+}
+
+// Field specification from https://www.icao.int/publications/Documents/9303_p4_cons_en.pdf
+fn encode_passport(passport: &Passport) -> Result<String, Error> {
+    let line1 = format!(
+        "{}{}{}",
+        document_code(&passport.document_code, &['P'])?,
+        field(&passport.country, 3)?,
+        names(&passport.surnames, &passport.given_names, 39)?
+    );
+
+    let number = field(&passport.passport_number, 9)?;
+    let birth_date = birth_date(passport.birth_date, &passport.birth_date_partial)?;
+    let expiry_date = date(&passport.expiry_date);
+    let personal_number = field(&passport.personal_number, 14)?;
+    let personal_number_check = if passport.personal_number.is_empty() {
+        '<'
+    } else {
+        check_digit(&personal_number)?
+    };
+
+    let mut line2 = format!(
+        "{}{}{}{}{}{}{}{}{}{}",
+        number,
+        check_digit(&number)?,
+        field(&passport.nationality, 3)?,
+        birth_date,
+        check_digit(&birth_date)?,
+        gender(&passport.gender, passport.sex),
+        expiry_date,
+        check_digit(&expiry_date)?,
+        personal_number,
+        personal_number_check
+    );
+
+    let composite = format!("{}{}{}", &line2[0..10], &line2[13..20], &line2[21..43]);
+    line2.push(check_digit(&composite)?);
+
+    Ok(line1 + &line2)
+}
+
+// Field specification from https://www.icao.int/publications/Documents/9303_p5_cons_en.pdf
+fn encode_identity_card(identity_card: &IdentityCard) -> Result<String, Error> {
+    let (number, optional_data) = document_number(
+        &identity_card.document_number,
+        &identity_card.optional_data,
+        15,
+    )?;
+    let line1 = format!(
+        "{}{}{}{}",
+        document_code(&identity_card.document_code, &['I', 'A', 'C'])?,
+        field(&identity_card.country, 3)?,
+        number,
+        optional_data
+    );
+
+    let birth_date = birth_date(identity_card.birth_date, &identity_card.birth_date_partial)?;
+    let expiry_date = date(&identity_card.expiry_date);
+    let mut line2 = format!(
+        "{}{}{}{}{}{}{}",
+        birth_date,
+        check_digit(&birth_date)?,
+        gender(&identity_card.gender, identity_card.sex),
+        expiry_date,
+        check_digit(&expiry_date)?,
+        field(&identity_card.nationality, 3)?,
+        field(&identity_card.optional_data_2, 11)?
+    );
+
+    let composite = format!(
+        "{}{}{}{}",
+        &line1[5..30],
+        &line2[0..7],
+        &line2[8..15],
+        &line2[18..29]
+    );
+    line2.push(check_digit(&composite)?);
+
+    let line3 = names(&identity_card.surnames, &identity_card.given_names, 30)?;
+
+    Ok(line1 + &line2 + &line3)
+}
+
+// Field specification from https://www.icao.int/publications/Documents/9303_p6_cons_en.pdf
+fn encode_td2_identity_card(identity_card: &IdentityCard) -> Result<String, Error> {
+    let line1 = format!(
+        "{}{}{}",
+        document_code(&identity_card.document_code, &['I', 'A', 'C'])?,
+        field(&identity_card.country, 3)?,
+        names(&identity_card.surnames, &identity_card.given_names, 31)?
+    );
+
+    let (number, optional_data) = document_number(
+        &identity_card.document_number,
+        &identity_card.optional_data,
+        7,
+    )?;
+    let birth_date = birth_date(identity_card.birth_date, &identity_card.birth_date_partial)?;
+    let expiry_date = date(&identity_card.expiry_date);
+    let mut line2 = format!(
+        "{}{}{}{}{}{}{}{}",
+        number,
+        field(&identity_card.nationality, 3)?,
+        birth_date,
+        check_digit(&birth_date)?,
+        gender(&identity_card.gender, identity_card.sex),
+        expiry_date,
+        check_digit(&expiry_date)?,
+        optional_data
+    );
+
+    let composite = format!("{}{}{}", &line2[0..10], &line2[13..20], &line2[21..35]);
+    line2.push(check_digit(&composite)?);
+
+    Ok(line1 + &line2)
+}
+
+// Field specification from https://www.icao.int/publications/Documents/9303_p7_cons_en.pdf
+fn encode_visa(visa: &Visa) -> Result<String, Error> {
+    let length = visa.format.line_length();
+    if visa.format != Format::MrvA && visa.format != Format::MrvB {
+        return Err(ErrorKind::InvalidFormat.into());
+    }
+
+    let line1 = format!(
+        "{}{}{}",
+        document_code(&visa.document_code, &['V'])?,
+        field(&visa.country, 3)?,
+        names(&visa.surnames, &visa.given_names, length - 5)?
+    );
+
+    let number = field(&visa.visa_number, 9)?;
+    let birth_date = birth_date(visa.birth_date, &visa.birth_date_partial)?;
+    let expiry_date = date(&visa.expiry_date);
+    let line2 = format!(
+        "{}{}{}{}{}{}{}{}{}",
+        number,
+        check_digit(&number)?,
+        field(&visa.nationality, 3)?,
+        birth_date,
+        check_digit(&birth_date)?,
+        gender(&visa.gender, visa.sex),
+        expiry_date,
+        check_digit(&expiry_date)?,
+        field(&visa.optional_data, length - 28)?
+    );
+
+    Ok(line1 + &line2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn round_trip(mrz: &str) -> String {
+        let document = parse(mrz, true).unwrap();
+        let encoded = encode(&document).unwrap();
+        assert_eq!(parse(&encoded, true).unwrap(), document);
+        encoded
+    }
+
+    #[test]
+    fn encode_passport() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122X1204159ZE184226B<<<<<10";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "P<CANMARTIN<<SARAH<<<<<<<<<<<<<<<<<<<<<<<<<<\
+                   ZE000509<9CAN8501019F2301147<<<<<<<<<<<<<<<8";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<JOHANNA<ELISABETHA\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_partial_birth_date() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408<<7F1204159ZE184226B<<<<<10";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO74<<<<1F1204159ZE184226B<<<<<18";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_inconsistent_birth_date() {
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        let mut passport = match document {
+            Document::Passport(passport) => passport,
+            _ => panic!("unexpected document"),
+        };
+        passport.birth_date = NaiveDate::from_ymd_opt(1975, 8, 12).unwrap();
+        assert_eq!(
+            encode(&Document::Passport(passport.clone()))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidBirthDate
+        );
+
+        passport.birth_date_partial = PartialDate::from(passport.birth_date);
+        let encoded = encode(&Document::Passport(passport)).unwrap();
+        assert_eq!(&encoded[57..63], "750812");
+    }
+
+    #[test]
+    fn encode_identity_card() {
+        let mrz = "I<NLDSPECI20212999999990<<<<<<\
+                   6503101F3108022NLDA1B2C3<<<<<9\
+                   DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_document_code() {
+        let mrz = "PDUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "ACUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                   D231458907UTO7408122F1204159<<<<<<<6";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "C<ITACA00000AA4<<<<<<<<<<<<<<<\
+                   6412308F2212304ITA<<<<<<<<<<<0\
+                   ROSSI<<BIANCA<<<<<<<<<<<<<<<<<";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        let mut passport = match document {
+            Document::Passport(passport) => passport,
+            _ => panic!("unexpected document"),
+        };
+        passport.document_code = "V".parse().unwrap();
+        assert_eq!(
+            encode(&Document::Passport(passport)).unwrap_err().kind(),
+            ErrorKind::InvalidDocumentType
+        );
+    }
+
+    #[test]
+    fn encode_identity_card_long_document_number() {
+        let mrz = "I<UTOD23145890<7349<ABC123<<<<\
+                   3407127M9507122UTO<<<<<<<<<<<3\
+                   STEVENSON<<PETER<JOHN<<<<<<<<<";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_td2_identity_card() {
+        let mrz = "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                   D231458907UTO7408122F1204159<<<<<<<6";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_visa() {
+        let mrz = "V<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L8988901C4XXX4009078F96121096ZE184226B<<<<<<";
+        assert_eq!(round_trip(mrz), mrz);
+
+        let mrz = "V<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                   L8988901C4XXX4009078F9612109<<<<<<<<";
+        assert_eq!(round_trip(mrz), mrz);
+    }
+
+    #[test]
+    fn encode_truncated_names() {
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        let mut passport = match document {
+            Document::Passport(passport) => passport,
+            _ => panic!("unexpected document"),
+        };
+        passport.surnames = vec!["VANDERSTEENHOVEN".into(), "MACALLISTER".into()];
+        passport.given_names = vec!["ALEXANDRA".into(), "CHRISTINA".into()];
+
+        let encoded = encode(&Document::Passport(passport.clone())).unwrap();
+        assert_eq!(&encoded[5..44], "VANDERSTEENHOVEN<MACALLISTER<<ALEXANDRA");
+
+        let mrz = round_trip(&encoded);
+        assert_eq!(mrz, encoded);
+
+        passport.given_names = vec!["ALEXANDRINA".into()];
+        let encoded = encode(&Document::Passport(passport.clone())).unwrap();
+        assert_eq!(&encoded[5..44], "VANDERSTEENHOVEN<MACALLISTER<<ALEXANDRI");
+
+        passport.surnames = vec!["WOLFESCHLEGELSTEINHAUSENBERGERDORFFVOGELSANG".into()];
+        let encoded = encode(&Document::Passport(passport)).unwrap();
+        assert_eq!(&encoded[5..44], "WOLFESCHLEGELSTEINHAUSENBERGERDORFFVOGE");
+    }
+
+    #[test]
+    fn encode_long_primary_identifier() {
+        for (mrz, names) in [
+            (
+                "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                 L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+                5..44,
+            ),
+            (
+                "I<NLDSPECI20212999999990<<<<<<\
+                 6503101F3108022NLDA1B2C3<<<<<9\
+                 DE<BRUIJN<<WILLEKE<LISELOTTE<<",
+                60..90,
+            ),
+            (
+                "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                 D231458907UTO7408122F1204159<<<<<<<6",
+                5..36,
+            ),
+            (
+                "V<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                 L8988901C4XXX4009078F96121096ZE184226B<<<<<<",
+                5..44,
+            ),
+            (
+                "V<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                 L8988901C4XXX4009078F9612109<<<<<<<<",
+                5..36,
+            ),
+        ] {
+            // The secondary identifier no longer fits after the primary one and its separator
+            for length in [names.len() - 2, names.len() - 1] {
+                let surnames = vec!["A".repeat(length)];
+                let mut document = parse(mrz, true).unwrap();
+                match &mut document {
+                    Document::Passport(passport) => passport.surnames = surnames,
+                    Document::IdentityCard(identity_card) => identity_card.surnames = surnames,
+                    Document::Visa(visa) => visa.surnames = surnames,
+                }
+
+                let encoded = encode(&document).unwrap();
+                assert_eq!(
+                    encoded[names.clone()],
+                    "A".repeat(length) + &"<".repeat(names.len() - length)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn encode_invalid_field() {
+        let document = parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            true,
+        )
+        .unwrap();
+
+        let mut passport = match document {
+            Document::Passport(passport) => passport,
+            _ => panic!("unexpected document"),
+        };
+        passport.passport_number = "l898902c3".into();
+        assert_eq!(
+            encode(&Document::Passport(passport.clone()))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidChar
+        );
+
+        passport.passport_number = "L898902C3X".into();
+        assert_eq!(
+            encode(&Document::Passport(passport)).unwrap_err().kind(),
+            ErrorKind::InvalidFormat
+        );
+    }
+}
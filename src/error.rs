@@ -1,12 +1,21 @@
+use crate::document::Field;
 use std::fmt;
 
-/// Parsing error
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error {
+/// Kind of parsing error
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorKind {
     /// Invalid or unsupported document type
     InvalidDocumentType,
     /// Invalid MRZ format
     InvalidFormat,
+    /// Line of a multi-line MRZ has the wrong length for the document format
+    InvalidLineLength,
+    /// Nationality is not a valid ICAO code
+    InvalidNationality,
+    /// Issuing state is not a valid ICAO code
+    InvalidIssuingState,
+    /// Sex is not `M`, `F`, `X` or `<`
+    InvalidSex,
     /// Invalid format for date of birth
     InvalidBirthDate,
     /// Invalid format for date of expiry
@@ -17,20 +26,231 @@ pub enum Error {
     ExpectedDigit,
     /// Encountered an invalid character (not [A-Z], [0-9] or <)
     InvalidChar,
+    /// Malformed BER-TLV data read from the chip
+    InvalidTlv,
 }
 
-impl fmt::Display for Error {
+impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use Error::*;
+        use ErrorKind::*;
         let message = match self {
             InvalidDocumentType => "invalid document type",
             InvalidFormat => "invalid MRZ format",
+            InvalidLineLength => "invalid length for MRZ line",
+            InvalidNationality => "invalid nationality",
+            InvalidIssuingState => "invalid issuing state",
+            InvalidSex => "invalid sex",
             InvalidBirthDate => "invalid date of birth",
             InvalidExpiryDate => "invalid date of expiry",
             BadCheckDigit => "provided MRZ failed check digit verification",
             ExpectedDigit => "expected digit at location but found something else",
             InvalidChar => "encountered a invalid character",
+            InvalidTlv => "malformed BER-TLV data",
         };
         write!(f, "{}", message)
     }
 }
+
+// Line length of a concatenated MRZ of the given total length (TD1, TD2 or TD3)
+fn line_length(length: usize) -> usize {
+    match length {
+        90 => 30,
+        72 => 36,
+        88 => 44,
+        length => length.max(1),
+    }
+}
+
+/// Location of an error within an MRZ
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Location {
+    /// Position within the concatenated MRZ
+    pub offset: usize,
+    /// Line of the MRZ (starting at 0)
+    pub line: usize,
+    /// Column within the line (starting at 0)
+    pub column: usize,
+}
+
+/// Parsing error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    kind: ErrorKind,
+    field: Option<Field>,
+    location: Option<Location>,
+    character: Option<char>,
+}
+
+impl Error {
+    // Error at `offset` of a concatenated MRZ, whose line length is implied by its total length
+    pub(crate) fn at(kind: ErrorKind, field: Option<Field>, mrz: &str, offset: usize) -> Error {
+        let line_length = line_length(mrz.len());
+
+        Error {
+            kind,
+            field,
+            location: Some(Location {
+                offset,
+                line: offset / line_length,
+                column: offset % line_length,
+            }),
+            character: mrz[offset.min(mrz.len())..].chars().next(),
+        }
+    }
+
+    // Error at `column` of `line` in a multi-line MRZ with lines of `line_length`
+    pub(crate) fn at_line(
+        kind: ErrorKind,
+        line: usize,
+        column: usize,
+        line_length: usize,
+    ) -> Error {
+        Error {
+            kind,
+            field: None,
+            location: Some(Location {
+                offset: line * line_length + column,
+                line,
+                column,
+            }),
+            character: None,
+        }
+    }
+
+    /// Kind of error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// MRZ field where the error was found, if known
+    pub fn field(&self) -> Option<Field> {
+        self.field
+    }
+
+    /// Location of the error within the MRZ, if known
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+
+    /// Offending character, if any
+    pub fn character(&self) -> Option<char> {
+        self.character
+    }
+
+    /// Render the MRZ one line at a time, with a caret under the offending character.
+    /// The MRZ may be given concatenated or as lines separated by newlines.
+    pub fn annotate(&self, mrz: &str) -> String {
+        let lines = mrz
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        let lines = match lines[..] {
+            [line] => line
+                .as_bytes()
+                .chunks(line_length(line.len()))
+                .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+                .collect::<Vec<_>>(),
+            _ => lines.into_iter().map(String::from).collect(),
+        };
+
+        let mut annotated = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            annotated.push_str(line);
+            annotated.push('\n');
+
+            if let Some(location) = self.location.filter(|location| location.line == index) {
+                annotated.push_str(&" ".repeat(location.column));
+                annotated.push_str("^\n");
+            }
+        }
+
+        annotated
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error {
+            kind,
+            field: None,
+            location: None,
+            character: None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        if let Some(location) = self.location {
+            write!(
+                f,
+                " at line {}, column {}",
+                location.line + 1,
+                location.column + 1
+            )?;
+        }
+
+        if let Some(character) = self.character {
+            write!(f, " (found '{}')", character)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<11";
+        let error = Error::at(ErrorKind::BadCheckDigit, Some(Field::Composite), mrz, 87);
+
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+        assert_eq!(error.field(), Some(Field::Composite));
+        assert_eq!(
+            error.location(),
+            Some(Location {
+                offset: 87,
+                line: 1,
+                column: 43,
+            })
+        );
+        assert_eq!(error.character(), Some('1'));
+        assert_eq!(
+            error.to_string(),
+            "provided MRZ failed check digit verification at line 2, column 44 (found '1')"
+        );
+    }
+
+    #[test]
+    fn annotate() {
+        let mrz = "C<ITACA00000AA4<<<<<<<<<<<<<<<\
+                   6412308F2212304ITA<<<<<<<<<<<0\
+                   ROSSI<<BIANCA<<<<<<<<<<<<<<<<<";
+        let error = Error::at(ErrorKind::InvalidBirthDate, Some(Field::BirthDate), mrz, 32);
+
+        assert_eq!(
+            error.annotate(mrz),
+            "C<ITACA00000AA4<<<<<<<<<<<<<<<\n\
+             6412308F2212304ITA<<<<<<<<<<<0\n\
+             \x20\x20^\n\
+             ROSSI<<BIANCA<<<<<<<<<<<<<<<<<\n"
+        );
+    }
+
+    #[test]
+    fn display_without_location() {
+        let error = Error::from(ErrorKind::InvalidFormat);
+        assert_eq!(error.to_string(), "invalid MRZ format");
+        assert_eq!(error.annotate("P<<"), "P<<\n");
+    }
+}
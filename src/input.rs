@@ -0,0 +1,127 @@
+use crate::error::{Error, ErrorKind};
+use std::borrow::Cow;
+
+// MRZ line lengths: TD1 (30), TD2 and MRV-B (36), TD3 and MRV-A (44)
+const TD1_LINE_LENGTH: usize = 30;
+const TWO_LINE_LENGTHS: [usize; 2] = [36, 44];
+
+// Accepts either a single concatenated MRZ or the MRZ split into lines (separated
+// by "\n" or "\r\n", possibly padded with whitespace) and returns the concatenated
+// MRZ expected by the parser, borrowing `data` when it already holds a single line.
+pub(crate) fn normalize(data: &str) -> Result<Cow<'_, str>, Error> {
+    let lines = data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+
+    match lines.len() {
+        0 => Err(ErrorKind::InvalidFormat.into()),
+        1 => Ok(Cow::Borrowed(lines[0])),
+        _ => join_lines(&lines).map(Cow::Owned),
+    }
+}
+
+// Validates the length of each line against the format implied by the number of
+// lines, reporting the first offending line.
+pub(crate) fn join_lines(lines: &[&str]) -> Result<String, Error> {
+    let lines = lines.iter().map(|line| line.trim()).collect::<Vec<_>>();
+
+    let line_length = match lines.len() {
+        3 => TD1_LINE_LENGTH,
+        2 => {
+            let lengths = [lines[0].chars().count(), lines[1].chars().count()];
+            if TWO_LINE_LENGTHS.contains(&lengths[0]) {
+                lengths[0]
+            } else if TWO_LINE_LENGTHS.contains(&lengths[1]) {
+                lengths[1]
+            } else {
+                return Err(Error::at_line(
+                    ErrorKind::InvalidLineLength,
+                    0,
+                    0,
+                    lengths[0],
+                ));
+            }
+        }
+        _ => return Err(ErrorKind::InvalidFormat.into()),
+    };
+
+    if let Some((index, line)) = lines
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.chars().count() != line_length)
+    {
+        // Point at the first missing or extra character
+        let column = line.chars().count().min(line_length);
+        return Err(Error::at_line(
+            ErrorKind::InvalidLineLength,
+            index,
+            column,
+            line_length,
+        ));
+    }
+
+    Ok(lines.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_single_line() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        assert!(matches!(normalize(mrz).unwrap(), Cow::Borrowed(data) if data == mrz));
+    }
+
+    #[test]
+    fn normalize_multiple_lines() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let lines = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<  \r\n\
+                     L898902C36UTO7408122F1204159ZE184226B<<<<<10\n";
+        assert_eq!(normalize(lines).unwrap(), mrz);
+    }
+
+    #[test]
+    fn join_lines_td1() {
+        let lines = [
+            "C<ITACA00000AA4<<<<<<<<<<<<<<<",
+            "6412308F2212304ITA<<<<<<<<<<<0",
+            "ROSSI<<BIANCA<<<<<<<<<<<<<<<<<",
+        ];
+        assert_eq!(join_lines(&lines).unwrap().len(), 90);
+    }
+
+    #[test]
+    fn join_lines_invalid_line_length() {
+        let lines = [
+            "C<ITACA00000AA4<<<<<<<<<<<<<<<",
+            "6412308F2212304ITA<<<<<<<<<<<0",
+            "ROSSI<<BIANCA<<<<<<<<<<<<<<<",
+        ];
+        let error = join_lines(&lines).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidLineLength);
+        assert_eq!(error.location().unwrap().line, 2);
+        assert_eq!(error.location().unwrap().column, 28);
+
+        let lines = [
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<",
+            "L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+        ];
+        let error = join_lines(&lines).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidLineLength);
+        assert_eq!(error.location().unwrap().line, 0);
+    }
+
+    #[test]
+    fn join_lines_invalid_line_count() {
+        let lines = ["P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<"; 4];
+        assert_eq!(
+            join_lines(&lines).unwrap_err().kind(),
+            ErrorKind::InvalidFormat
+        );
+    }
+}
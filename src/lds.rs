@@ -0,0 +1,693 @@
+//! Data groups and files of the Logical Data Structure (LDS) stored on the chip of an eMRTD, as defined in
+//! [ICAO Document 9303 Part 10](https://www.icao.int/publications/Documents/9303_p10_cons_en.pdf).
+
+use crate::document::Document;
+use crate::encoder::encode;
+use crate::error::{Error, ErrorKind};
+use crate::parser;
+use std::convert::TryFrom;
+use std::str;
+
+/// Tag of data group 1 (DG1)
+pub const DG1_TAG: u32 = 0x61;
+/// Tag of the MRZ data element within DG1
+pub const MRZ_TAG: u32 = 0x5f1f;
+/// Tag of EF.COM
+pub const COM_TAG: u32 = 0x60;
+/// Tag of EF.SOD
+pub const SOD_TAG: u32 = 0x77;
+
+// Tags of data groups 1 to 16, as listed in EF.COM
+const DATA_GROUP_TAGS: [u8; 16] = [
+    0x61, 0x75, 0x63, 0x76, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70,
+];
+
+// DER tags of the ASN.1 types used in EF.SOD
+const INTEGER: u32 = 0x02;
+const OCTET_STRING: u32 = 0x04;
+const OBJECT_IDENTIFIER: u32 = 0x06;
+const SEQUENCE: u32 = 0x30;
+const SET: u32 = 0x31;
+const EXPLICIT_0: u32 = 0xa0;
+
+// id-signedData
+const SIGNED_DATA_OID: &str = "1.2.840.113549.1.7.2";
+// id-icao-mrtd-security-ldsSecurityObject
+const LDS_SECURITY_OBJECT_OID: &str = "2.23.136.1.1.1";
+
+// Deepest nesting of indefinite-length values accepted, well beyond what LDS structures use
+const MAX_INDEFINITE_DEPTH: usize = 16;
+
+// BER-TLV data object
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub(crate) tag: u32,
+    pub(crate) value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    // Reads the data object at the start of `data`, returning it with the remaining bytes
+    pub(crate) fn read(data: &'a [u8]) -> Result<(Tlv<'a>, &'a [u8]), Error> {
+        Tlv::read_nested(data, 0)
+    }
+
+    // Reads a data object nested within `depth` indefinite-length values
+    fn read_nested(data: &'a [u8], depth: usize) -> Result<(Tlv<'a>, &'a [u8]), Error> {
+        let error = || Error::from(ErrorKind::InvalidTlv);
+
+        let (&first, mut rest) = data.split_first().ok_or_else(error)?;
+        let mut tag = u32::from(first);
+        let constructed = first & 0x20 != 0;
+
+        // Tag numbers of 31 and above continue in subsequent bytes, up to one without bit 8 set
+        if first & 0x1f == 0x1f {
+            loop {
+                let (&byte, remaining) = rest.split_first().ok_or_else(error)?;
+                if tag > 0xffff {
+                    return Err(error());
+                }
+                tag = tag << 8 | u32::from(byte);
+                rest = remaining;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+        }
+
+        let (&first, mut rest) = rest.split_first().ok_or_else(error)?;
+        let length = match first {
+            0x00..=0x7f => usize::from(first),
+            // Indefinite length (BER only, for constructed values): the contained data
+            // objects run up to an end-of-contents marker
+            0x80 if constructed && depth < MAX_INDEFINITE_DEPTH => {
+                let mut contents = rest;
+                while !contents.starts_with(&[0x00, 0x00]) {
+                    contents = Tlv::read_nested(contents, depth + 1)?.1;
+                }
+
+                let value = &rest[..rest.len() - contents.len()];
+                return Ok((Tlv { tag, value }, &contents[2..]));
+            }
+            0x81..=0x84 => {
+                let count = usize::from(first & 0x7f);
+                if rest.len() < count {
+                    return Err(error());
+                }
+                let (bytes, remaining) = rest.split_at(count);
+                rest = remaining;
+                bytes
+                    .iter()
+                    .fold(0, |length, &byte| length << 8 | usize::from(byte))
+            }
+            _ => return Err(error()),
+        };
+
+        if rest.len() < length {
+            return Err(error());
+        }
+
+        let (value, rest) = rest.split_at(length);
+        Ok((Tlv { tag, value }, rest))
+    }
+
+    // Reads the data object at the start of `data`, which must have the given tag
+    pub(crate) fn read_tagged(data: &'a [u8], tag: u32) -> Result<Tlv<'a>, Error> {
+        match Tlv::read(data)? {
+            (tlv, _) if tlv.tag == tag => Ok(tlv),
+            _ => Err(ErrorKind::InvalidTlv.into()),
+        }
+    }
+
+    // Data objects contained in a constructed value
+    pub(crate) fn children(&self) -> Result<Vec<Tlv<'a>>, Error> {
+        let mut children = Vec::new();
+        let mut data = self.value;
+
+        while !data.is_empty() {
+            let (child, rest) = Tlv::read(data)?;
+            children.push(child);
+            data = rest;
+        }
+
+        Ok(children)
+    }
+
+    // Appends the encoded data object, using the shortest form of the tag and length
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        let tag = self.tag.to_be_bytes();
+        let start = tag.iter().position(|&byte| byte != 0).unwrap_or(3);
+        out.extend_from_slice(&tag[start..]);
+
+        let length = self.value.len();
+        if length < 0x80 {
+            out.push(length as u8);
+        } else {
+            let bytes = length.to_be_bytes();
+            let start = bytes.iter().position(|&byte| byte != 0).unwrap_or(0);
+            out.push(0x80 | (bytes.len() - start) as u8);
+            out.extend_from_slice(&bytes[start..]);
+        }
+
+        out.extend_from_slice(self.value);
+    }
+
+    // First contained data object with the given tag
+    pub(crate) fn child(&self, tag: u32) -> Result<Tlv<'a>, Error> {
+        self.children()?
+            .into_iter()
+            .find(|child| child.tag == tag)
+            .ok_or_else(|| ErrorKind::InvalidTlv.into())
+    }
+}
+
+/// MRZ stored in data group 1 (DG1), as the concatenated lines
+pub fn dg1_mrz(data: &[u8]) -> Result<&str, Error> {
+    let mrz = Tlv::read_tagged(data, DG1_TAG)?.child(MRZ_TAG)?;
+    str::from_utf8(mrz.value).map_err(|_| ErrorKind::InvalidChar.into())
+}
+
+/// Decode data group 1 (DG1) into the travel document whose MRZ it holds.
+/// Performs error checking using the included check digits.
+pub fn decode_dg1(data: &[u8]) -> Result<Document, Error> {
+    parser::parse(dg1_mrz(data)?, true)
+}
+
+/// Encode a travel document into data group 1 (DG1), holding its MRZ with the lines concatenated
+pub fn encode_dg1(document: &Document) -> Result<Vec<u8>, Error> {
+    let mrz = encode(document)?;
+
+    let mut value = Vec::new();
+    Tlv {
+        tag: MRZ_TAG,
+        value: mrz.as_bytes(),
+    }
+    .write(&mut value);
+
+    let mut data = Vec::new();
+    Tlv {
+        tag: DG1_TAG,
+        value: &value,
+    }
+    .write(&mut data);
+    Ok(data)
+}
+
+/// Contents of EF.COM, listing the data groups present on the chip
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Com {
+    /// LDS version as four digits `aabb` for version aa.bb (e.g. `0107`)
+    pub lds_version: String,
+    /// Unicode version as six digits `aabbcc` for version aa.bb.cc (e.g. `040000`)
+    pub unicode_version: String,
+    /// Tags of the data groups present
+    pub tags: Vec<u8>,
+}
+
+impl Com {
+    /// Numbers of the data groups present, skipping unknown tags
+    pub fn data_groups(&self) -> Vec<u8> {
+        self.tags
+            .iter()
+            .filter_map(|&tag| data_group_number(tag))
+            .collect()
+    }
+}
+
+/// Document Security Object (EF.SOD): the hashes of the data groups, as signed by the issuer
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Sod {
+    /// Version of the LDSSecurityObject
+    pub version: u32,
+    /// Object identifier of the hash algorithm in dotted notation
+    /// (e.g. `2.16.840.1.101.3.4.2.1` for SHA-256)
+    pub hash_algorithm: String,
+    /// Hashes of the data groups
+    pub data_group_hashes: Vec<DataGroupHash>,
+}
+
+impl Sod {
+    /// Hash of the given data group, if present
+    pub fn hash(&self, data_group: u8) -> Option<&[u8]> {
+        self.data_group_hashes
+            .iter()
+            .find(|hash| hash.data_group == data_group)
+            .map(|hash| hash.hash.as_slice())
+    }
+}
+
+/// Hash of a data group in the Document Security Object
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DataGroupHash {
+    /// Data group number (1 to 16)
+    pub data_group: u8,
+    /// Hash of the complete data group
+    pub hash: Vec<u8>,
+}
+
+/// Number of the data group with the given tag
+pub fn data_group_number(tag: u8) -> Option<u8> {
+    DATA_GROUP_TAGS
+        .iter()
+        .position(|&data_group_tag| data_group_tag == tag)
+        .map(|index| index as u8 + 1)
+}
+
+/// Decode EF.COM
+pub fn decode_com(data: &[u8]) -> Result<Com, Error> {
+    let children = Tlv::read_tagged(data, COM_TAG)?.children()?;
+    let child = |tag| {
+        children
+            .iter()
+            .find(|child| child.tag == tag)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidTlv))
+    };
+    let text = |tag| {
+        str::from_utf8(child(tag)?.value)
+            .map(String::from)
+            .map_err(|_| Error::from(ErrorKind::InvalidTlv))
+    };
+
+    Ok(Com {
+        lds_version: text(0x5f01)?,
+        unicode_version: text(0x5f36)?,
+        tags: child(0x5c)?.value.to_vec(),
+    })
+}
+
+/// Decode the LDSSecurityObject held by EF.SOD, which may use BER indefinite lengths.
+/// The CMS signature and certificates are not verified.
+pub fn decode_sod(data: &[u8]) -> Result<Sod, Error> {
+    // ContentInfo
+    let content_info = sequence(Tlv::read(Tlv::read_tagged(data, SOD_TAG)?.value)?.0)?;
+    match content_info.as_slice() {
+        [content_type, content] if oid(content_type)? == SIGNED_DATA_OID => {
+            // SignedData: version, digestAlgorithms, encapContentInfo, ...
+            let signed_data = sequence(explicit(content)?)?;
+            let encap_content_info = match signed_data.as_slice() {
+                [version, digest_algorithms, encap_content_info, ..]
+                    if version.tag == INTEGER && digest_algorithms.tag == SET =>
+                {
+                    sequence(*encap_content_info)?
+                }
+                _ => return Err(ErrorKind::InvalidTlv.into()),
+            };
+
+            match encap_content_info.as_slice() {
+                [content_type, content] if oid(content_type)? == LDS_SECURITY_OBJECT_OID => {
+                    security_object(expect(explicit(content)?, OCTET_STRING)?.value)
+                }
+                _ => Err(ErrorKind::InvalidTlv.into()),
+            }
+        }
+        _ => Err(ErrorKind::InvalidTlv.into()),
+    }
+}
+
+// LDSSecurityObject: version, hashAlgorithm, dataGroupHashValues and an optional ldsVersionInfo
+fn security_object(data: &[u8]) -> Result<Sod, Error> {
+    match sequence(Tlv::read(data)?.0)?.as_slice() {
+        [version, hash_algorithm, data_group_hashes, ..] => {
+            let hash_algorithm = match expect(*hash_algorithm, SEQUENCE)?.children()?.first() {
+                Some(algorithm) => oid(algorithm)?,
+                None => return Err(ErrorKind::InvalidTlv.into()),
+            };
+
+            let data_group_hashes = expect(*data_group_hashes, SEQUENCE)?
+                .children()?
+                .iter()
+                .map(
+                    |data_group_hash| match sequence(*data_group_hash)?.as_slice() {
+                        [number, hash] => Ok(DataGroupHash {
+                            data_group: u8::try_from(integer(number)?)
+                                .map_err(|_| Error::from(ErrorKind::InvalidTlv))?,
+                            hash: expect(*hash, OCTET_STRING)?.value.to_vec(),
+                        }),
+                        _ => Err(ErrorKind::InvalidTlv.into()),
+                    },
+                )
+                .collect::<Result<_, Error>>()?;
+
+            Ok(Sod {
+                version: integer(version)?,
+                hash_algorithm,
+                data_group_hashes,
+            })
+        }
+        _ => Err(ErrorKind::InvalidTlv.into()),
+    }
+}
+
+fn expect(tlv: Tlv<'_>, tag: u32) -> Result<Tlv<'_>, Error> {
+    if tlv.tag == tag {
+        Ok(tlv)
+    } else {
+        Err(ErrorKind::InvalidTlv.into())
+    }
+}
+
+// Elements of a SEQUENCE
+fn sequence(tlv: Tlv<'_>) -> Result<Vec<Tlv<'_>>, Error> {
+    expect(tlv, SEQUENCE)?.children()
+}
+
+// Value of an explicitly tagged [0] element
+fn explicit<'a>(tlv: &Tlv<'a>) -> Result<Tlv<'a>, Error> {
+    Tlv::read(expect(*tlv, EXPLICIT_0)?.value).map(|(inner, _)| inner)
+}
+
+// Non-negative INTEGER that fits in 32 bits
+fn integer(tlv: &Tlv) -> Result<u32, Error> {
+    match expect(*tlv, INTEGER)?.value {
+        [first, ..] if first & 0x80 != 0 => Err(ErrorKind::InvalidTlv.into()),
+        [0, rest @ ..] if rest.len() <= 4 => Ok(be_u32(rest)),
+        value @ [_, ..] if value.len() <= 4 => Ok(be_u32(value)),
+        _ => Err(ErrorKind::InvalidTlv.into()),
+    }
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| value << 8 | u32::from(byte))
+}
+
+// OBJECT IDENTIFIER in dotted notation
+fn oid(tlv: &Tlv) -> Result<String, Error> {
+    let mut arcs = Vec::new();
+    let mut arc: u64 = 0;
+    let value = expect(*tlv, OBJECT_IDENTIFIER)?.value;
+
+    for &byte in value {
+        if arc > u64::from(u32::MAX) {
+            return Err(ErrorKind::InvalidTlv.into());
+        }
+        arc = arc << 7 | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                // The first subidentifier combines the first two arcs
+                let first = (arc / 40).min(2);
+                arcs.push(first);
+                arcs.push(arc - first * 40);
+            } else {
+                arcs.push(arc);
+            }
+            arc = 0;
+        }
+    }
+
+    if value.last().is_none_or(|byte| byte & 0x80 != 0) {
+        return Err(ErrorKind::InvalidTlv.into());
+    }
+
+    Ok(arcs
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dg1(mrz: &str) -> Vec<u8> {
+        let mut data = vec![0x61, mrz.len() as u8 + 3, 0x5f, 0x1f, mrz.len() as u8];
+        data.extend_from_slice(mrz.as_bytes());
+        data
+    }
+
+    #[test]
+    fn decode_passport() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let data = dg1(mrz);
+        assert_eq!(&data[..5], [0x61, 0x5b, 0x5f, 0x1f, 0x58]);
+
+        assert_eq!(dg1_mrz(&data).unwrap(), mrz);
+        assert_eq!(
+            decode_dg1(&data).unwrap(),
+            parser::parse(mrz, true).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_identity_card() {
+        let mrz = "I<NLDSPECI20212999999990<<<<<<\
+                   6503101F3108022NLDA1B2C3<<<<<9\
+                   DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        match decode_dg1(&dg1(mrz)).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.document_number, "SPECI2021");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn long_form_length() {
+        let mut data = vec![0x61, 0x81, 0x03, 0x5f, 0x1f, 0x81, 0x00];
+        let (tlv, rest) = Tlv::read(&data).unwrap();
+        assert_eq!(tlv.tag, 0x61);
+        assert_eq!(tlv.value, [0x5f, 0x1f, 0x81]);
+        assert_eq!(rest, [0x00]);
+
+        data.truncate(6);
+        assert_eq!(
+            Tlv::read(&data[3..]).unwrap_err().kind(),
+            ErrorKind::InvalidTlv
+        );
+    }
+
+    #[test]
+    fn indefinite_length() {
+        let data = [0x61, 0x80, 0x5f, 0x1f, 0x01, 0x41, 0x00, 0x00, 0x01, 0x00];
+        let (tlv, rest) = Tlv::read(&data).unwrap();
+        assert_eq!(tlv.tag, 0x61);
+        assert_eq!(tlv.value, [0x5f, 0x1f, 0x01, 0x41]);
+        assert_eq!(rest, [0x01, 0x00]);
+        assert_eq!(dg1_mrz(&data).unwrap(), "A");
+
+        // Nested indefinite lengths
+        let data = [
+            0x30, 0x80, 0x31, 0x80, 0x02, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let (tlv, rest) = Tlv::read(&data).unwrap();
+        assert_eq!(tlv.children().unwrap()[0].value, [0x02, 0x01, 0x03]);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        for mrz in [
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+            "I<UTOD23145890<7349<ABC123<<<<\
+             3407127M9507122UTO<<<<<<<<<<<3\
+             STEVENSON<<PETER<JOHN<<<<<<<<<",
+        ] {
+            let document = parser::parse(mrz, true).unwrap();
+            let data = encode_dg1(&document).unwrap();
+            assert_eq!(data, dg1(mrz));
+            assert_eq!(decode_dg1(&data).unwrap(), document);
+        }
+    }
+
+    #[test]
+    fn write_long_form_length() {
+        for (length, header) in [
+            (0x7f, &[0x5f, 0x1f, 0x7f][..]),
+            (0x80, &[0x5f, 0x1f, 0x81, 0x80]),
+            (0x100, &[0x5f, 0x1f, 0x82, 0x01, 0x00]),
+        ] {
+            let value = vec![0x3c; length];
+            let mut data = Vec::new();
+            Tlv {
+                tag: MRZ_TAG,
+                value: &value,
+            }
+            .write(&mut data);
+
+            assert_eq!(&data[..header.len()], header);
+            assert_eq!(data.len(), header.len() + length);
+            assert_eq!(Tlv::read(&data).unwrap().0.value, &value[..]);
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        for data in [
+            &[][..],
+            &[0x61],
+            &[0x61, 0x05, 0x5f, 0x1f, 0x01],
+            &[0x61, 0x80, 0x5f, 0x1f, 0x01, 0x41],
+            &[0x61, 0x05, 0x5f, 0x1f, 0x80, 0x00, 0x00],
+            &[0x61, 0x85, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00],
+            &[0x61, 0x02, 0x5f, 0x1f],
+            &[0x61, 0x03, 0x5f, 0x1e, 0x00],
+            &[0x60, 0x03, 0x5f, 0x1f, 0x00],
+        ] {
+            assert_eq!(dg1_mrz(data).unwrap_err().kind(), ErrorKind::InvalidTlv);
+        }
+
+        // Indefinite lengths nested too deeply
+        let data = [0x30, 0x80].repeat(200_000);
+        assert_eq!(decode_sod(&data).unwrap_err().kind(), ErrorKind::InvalidTlv);
+        let data = [&[0x30, 0x80].repeat(16)[..], &[0x00, 0x00].repeat(16)].concat();
+        assert!(Tlv::read(&data).is_ok());
+        let data = [&[0x30, 0x80].repeat(17)[..], &[0x00, 0x00].repeat(17)].concat();
+        assert_eq!(Tlv::read(&data).unwrap_err().kind(), ErrorKind::InvalidTlv);
+    }
+
+    #[test]
+    fn invalid_mrz() {
+        let mut data = dg1("P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                            L898902C36UTO7408122F1204159ZE184226B<<<<<11");
+        assert_eq!(
+            decode_dg1(&data).unwrap_err().kind(),
+            ErrorKind::BadCheckDigit
+        );
+
+        data[10] = 0xff;
+        assert_eq!(dg1_mrz(&data).unwrap_err().kind(), ErrorKind::InvalidChar);
+    }
+
+    fn tlv(tag: u32, value: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        Tlv { tag, value }.write(&mut data);
+        data
+    }
+
+    // Example from ICAO 9303 Part 10, listing DG1, DG2, DG3 and DG4
+    #[test]
+    fn com() {
+        let data = [
+            0x60, 0x16, 0x5f, 0x01, 0x04, 0x30, 0x31, 0x30, 0x37, 0x5f, 0x36, 0x06, 0x30, 0x34,
+            0x30, 0x30, 0x30, 0x30, 0x5c, 0x04, 0x61, 0x75, 0x63, 0x76,
+        ];
+
+        let com = decode_com(&data).unwrap();
+        assert_eq!(com.lds_version, "0107");
+        assert_eq!(com.unicode_version, "040000");
+        assert_eq!(com.tags, [0x61, 0x75, 0x63, 0x76]);
+        assert_eq!(com.data_groups(), [1, 2, 3, 4]);
+
+        assert_eq!(
+            decode_com(&data[..20]).unwrap_err().kind(),
+            ErrorKind::InvalidTlv
+        );
+    }
+
+    // BER encoding with an indefinite length
+    fn indefinite(tag: u32, value: &[u8]) -> Vec<u8> {
+        let mut data = tlv(tag, &[]);
+        data.pop();
+        data.push(0x80);
+        data.extend_from_slice(value);
+        data.extend_from_slice(&[0x00, 0x00]);
+        data
+    }
+
+    fn sod(hashes: &[(u8, &[u8])]) -> Vec<u8> {
+        encode_sod(hashes, tlv)
+    }
+
+    // EF.SOD with its outer constructed values encoded by `constructed`
+    fn encode_sod(hashes: &[(u8, &[u8])], constructed: fn(u32, &[u8]) -> Vec<u8>) -> Vec<u8> {
+        // id-sha256
+        let sha256 = [
+            0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+        ];
+        let data_group_hashes: Vec<u8> = hashes
+            .iter()
+            .flat_map(|(number, hash)| {
+                tlv(
+                    SEQUENCE,
+                    &[tlv(INTEGER, &[*number]), tlv(OCTET_STRING, hash)].concat(),
+                )
+            })
+            .collect();
+        let security_object = tlv(
+            SEQUENCE,
+            &[
+                tlv(INTEGER, &[0]),
+                tlv(SEQUENCE, &sha256),
+                tlv(SEQUENCE, &data_group_hashes),
+            ]
+            .concat(),
+        );
+
+        let encap_content_info = constructed(
+            SEQUENCE,
+            &[
+                tlv(OBJECT_IDENTIFIER, &[0x67, 0x81, 0x08, 0x01, 0x01, 0x01]),
+                constructed(EXPLICIT_0, &tlv(OCTET_STRING, &security_object)),
+            ]
+            .concat(),
+        );
+        let signed_data = constructed(
+            SEQUENCE,
+            &[
+                tlv(INTEGER, &[3]),
+                tlv(SET, &tlv(SEQUENCE, &sha256)),
+                encap_content_info,
+                tlv(SET, &[]),
+            ]
+            .concat(),
+        );
+        let content_info = constructed(
+            SEQUENCE,
+            &[
+                tlv(
+                    OBJECT_IDENTIFIER,
+                    &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02],
+                ),
+                constructed(EXPLICIT_0, &signed_data),
+            ]
+            .concat(),
+        );
+
+        constructed(SOD_TAG, &content_info)
+    }
+
+    #[test]
+    fn security_object() {
+        let dg1_hash = [0x11; 32];
+        let dg2_hash = [0x22; 32];
+        let sod = decode_sod(&sod(&[(1, &dg1_hash), (2, &dg2_hash)])).unwrap();
+
+        assert_eq!(sod.version, 0);
+        assert_eq!(sod.hash_algorithm, "2.16.840.1.101.3.4.2.1");
+        assert_eq!(sod.data_group_hashes.len(), 2);
+        assert_eq!(sod.hash(1), Some(&dg1_hash[..]));
+        assert_eq!(sod.hash(2), Some(&dg2_hash[..]));
+        assert_eq!(sod.hash(3), None);
+    }
+
+    #[test]
+    fn security_object_indefinite_length() {
+        let hashes: [(u8, &[u8]); 2] = [(1, &[0x11; 32]), (2, &[0x22; 32])];
+        assert_eq!(
+            decode_sod(&encode_sod(&hashes, indefinite)).unwrap(),
+            decode_sod(&sod(&hashes)).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_security_object() {
+        let data = sod(&[(1, &[0x11; 32])]);
+        assert_eq!(
+            decode_sod(&data[..data.len() - 1]).unwrap_err().kind(),
+            ErrorKind::InvalidTlv
+        );
+
+        // Data group number that is not a non-negative INTEGER
+        let data = sod(&[(0x81, &[0x11; 32])]);
+        assert_eq!(decode_sod(&data).unwrap_err().kind(), ErrorKind::InvalidTlv);
+
+        // DG1 instead of EF.SOD
+        let data = dg1("P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                        L898902C36UTO7408122F1204159ZE184226B<<<<<10");
+        assert_eq!(decode_sod(&data).unwrap_err().kind(), ErrorKind::InvalidTlv);
+    }
+}
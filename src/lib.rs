@@ -1,21 +1,79 @@
 //! A Rust parser for the machine-readable zone (MRZ) of machine-readable travel documents (MRTD)
 //! as defined by ICAO Document 9303.
 
+pub mod bac;
+pub mod check_digit;
+mod correction;
+mod country;
 mod document;
+mod document_ref;
+mod encoder;
 mod error;
+mod input;
+pub mod lds;
+mod options;
+pub mod pace;
 mod parser;
+mod raw;
+mod scanner;
+mod validation;
 
+pub use correction::{parse_with_correction, CorrectedDocument, Correction};
+pub use country::CountryCode;
 pub use document::*;
-pub use error::Error;
+pub use document_ref::DocumentRef;
+pub use encoder::encode;
+pub use error::{Error, ErrorKind, Location};
+pub use options::{CenturyPolicy, ParseOptions};
+pub use raw::{RawMrz, Span};
+pub use scanner::{find_mrz, Candidate};
+pub use validation::{validate, DecodedFields, ValidationFailure, ValidationReport};
 
 /// Parse a Machine-readable Zone (MRZ) returning the corresponding travel document.
+/// The MRZ may be given either as a single string or as lines separated by newlines.
 /// Performs error checking using the included check digits.
 pub fn parse(data: &str) -> Result<Document, Error> {
     parser::parse(data, true)
 }
 
 /// Parse a Machine-readable Zone (MRZ) returning the corresponding travel document.
+/// The MRZ may be given either as a single string or as lines separated by newlines.
 /// Does not perform error checking using the included check digits.
 pub fn parse_without_checks(data: &str) -> Result<Document, Error> {
     parser::parse(data, false)
 }
+
+/// Parse a Machine-readable Zone (MRZ) returning the corresponding travel document,
+/// using the given options for check digits and resolution of two-digit years.
+pub fn parse_with_options(data: &str, options: &ParseOptions) -> Result<Document, Error> {
+    parser::parse_with_options(data, options)
+}
+
+/// Parse a Machine-readable Zone (MRZ) returning a travel document that borrows its
+/// fields from `data`, which must hold the MRZ as a single string.
+/// Performs error checking using the included check digits.
+pub fn parse_ref(data: &str) -> Result<DocumentRef<'_>, Error> {
+    parser::parse_ref(data, true)
+}
+
+/// Parse a Machine-readable Zone (MRZ) returning a travel document that borrows its
+/// fields from `data`, using the given options for check digits and resolution of
+/// two-digit years.
+pub fn parse_ref_with_options<'a>(
+    data: &'a str,
+    options: &ParseOptions,
+) -> Result<DocumentRef<'a>, Error> {
+    parser::parse_ref_with_options(data, options)
+}
+
+/// Parse the lines of a Machine-readable Zone (MRZ) returning the corresponding travel document.
+/// Performs error checking using the included check digits.
+pub fn parse_lines(lines: &[&str]) -> Result<Document, Error> {
+    parser::parse(&input::join_lines(lines)?, true)
+}
+
+/// Parse the lines of a Machine-readable Zone (MRZ) returning the corresponding travel document.
+/// Does not perform error checking using the included check digits.
+pub fn parse_lines_without_checks(lines: &[&str]) -> Result<Document, Error> {
+    parser::parse(&input::join_lines(lines)?, false)
+}
@@ -0,0 +1,45 @@
+use chrono::NaiveDate;
+
+/// How the century of a two-digit year (YY) is resolved
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CenturyPolicy {
+    /// Most recent date not after the reference date
+    Past,
+    /// Date within the 100 year window starting the given number of years before the reference date
+    Window {
+        /// Years before the reference date at which the window starts
+        years_before: u32,
+    },
+}
+
+/// Options for parsing a Machine-readable Zone (MRZ)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseOptions {
+    /// Perform error checking using the included check digits
+    pub check: bool,
+    /// Date against which two-digit years are resolved (defaults to the current date)
+    pub reference_date: Option<NaiveDate>,
+    /// Century policy for the date of birth (defaults to [`CenturyPolicy::Past`])
+    pub birth_date_policy: CenturyPolicy,
+    /// Century policy for the date of expiry (defaults to a window starting 80 years in the past,
+    /// as documents expire at most a few years ahead)
+    pub expiry_date_policy: CenturyPolicy,
+    /// Reject issuing state codes not listed in ICAO 9303 (see [`CountryCode`](crate::CountryCode)),
+    /// and nationality codes even when not checking
+    pub reject_unknown_country_codes: bool,
+    /// Keep the MRZ as read and the location of every field in the parsed document
+    pub keep_raw: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            check: true,
+            reference_date: None,
+            birth_date_policy: CenturyPolicy::Past,
+            expiry_date_policy: CenturyPolicy::Window { years_before: 80 },
+            reject_unknown_country_codes: false,
+            keep_raw: false,
+        }
+    }
+}
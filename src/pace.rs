@@ -0,0 +1,138 @@
+//! Password Authenticated Connection Establishment (PACE) key derivation from the MRZ as defined in
+//! [ICAO Document 9303 Part 11](https://www.icao.int/publications/Documents/9303_p11_cons_en.pdf) (sections 4.4 and 9.7)
+//! and BSI TR-03110.
+
+use crate::bac::{self, mrz_information};
+use crate::document::Document;
+use crate::error::Error;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// Block cipher for which a key is derived
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Cipher {
+    /// Two-key 3DES (112 bits)
+    TripleDes,
+    /// AES-128
+    Aes128,
+    /// AES-192
+    Aes192,
+    /// AES-256
+    Aes256,
+}
+
+/// Counter for deriving the encryption key (K_enc)
+pub const ENC_COUNTER: u32 = 1;
+/// Counter for deriving the MAC key (K_mac)
+pub const MAC_COUNTER: u32 = 2;
+/// Counter for deriving the key that encrypts the nonce from the password (K_π)
+pub const PASSWORD_COUNTER: u32 = 3;
+
+/// Password derived from the MRZ (K): the SHA-1 hash of the MRZ information
+pub fn password(document: &Document) -> Result<[u8; 20], Error> {
+    Ok(Sha1::digest(mrz_information(document)?.as_bytes()).into())
+}
+
+/// Key derivation function KDF(K, c): SHA-1 for 3DES and AES-128, SHA-256 for
+/// AES-192 and AES-256, truncated to the key length. 3DES keys have their parity adjusted.
+pub fn derive_key(secret: &[u8], counter: u32, cipher: Cipher) -> Vec<u8> {
+    match cipher {
+        Cipher::TripleDes => bac::derive_key(secret, counter).to_vec(),
+        Cipher::Aes128 => {
+            let hash = Sha1::new()
+                .chain_update(secret)
+                .chain_update(counter.to_be_bytes())
+                .finalize();
+            hash[..16].to_vec()
+        }
+        Cipher::Aes192 | Cipher::Aes256 => {
+            let hash = Sha256::new()
+                .chain_update(secret)
+                .chain_update(counter.to_be_bytes())
+                .finalize();
+            let length = if cipher == Cipher::Aes192 { 24 } else { 32 };
+            hash[..length].to_vec()
+        }
+    }
+}
+
+/// Key that encrypts the nonce (K_π), derived from the MRZ password
+pub fn password_key(document: &Document, cipher: Cipher) -> Result<Vec<u8>, Error> {
+    Ok(derive_key(&password(document)?, PASSWORD_COUNTER, cipher))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+    }
+
+    // Document number T22000129, date of birth 640812 and date of expiry 101031
+    fn document() -> Document {
+        parse(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             T220001293UTO6408125F1010318<<<<<<<<<<<<<<<6",
+            true,
+        )
+        .unwrap()
+    }
+
+    // Worked example from ICAO 9303 Part 11, Appendix G.1
+    #[test]
+    fn icao_example() {
+        let document = document();
+
+        assert_eq!(
+            mrz_information(&document).unwrap(),
+            "T22000129364081251010318"
+        );
+        assert_eq!(
+            hex(&password(&document).unwrap()),
+            "7E2D2A41C74EA0B38CD36F863939BFA8E9032AAD"
+        );
+        assert_eq!(
+            hex(&password_key(&document, Cipher::Aes128).unwrap()),
+            "89DED1B26624EC1E634C1989302849DD"
+        );
+    }
+
+    // Only the AES-128 key of the example above is published. The other expected values
+    // are regression values from this implementation, not published test vectors.
+    #[test]
+    fn password_key_ciphers() {
+        let document = document();
+        let aes_128 = password_key(&document, Cipher::Aes128).unwrap();
+        let aes_256 = password_key(&document, Cipher::Aes256).unwrap();
+
+        // 3DES uses the SHA-1 hash of AES-128, with the parity bits adjusted
+        let mut triple_des = aes_128;
+        bac::adjust_parity(&mut triple_des);
+        assert_eq!(
+            password_key(&document, Cipher::TripleDes).unwrap(),
+            triple_des
+        );
+        assert_eq!(hex(&triple_des), "89DFD0B36725EC1F624C1989312949DC");
+
+        // AES-192 takes the first 24 bytes of the SHA-256 hash used for AES-256
+        assert_eq!(
+            password_key(&document, Cipher::Aes192).unwrap(),
+            aes_256[..24]
+        );
+        assert_eq!(
+            hex(&aes_256),
+            "D79A23C126202AC9051FEBFBC0E8A03B1C6645D85752B4B71408FA229AB6D56B"
+        );
+    }
+
+    #[test]
+    fn derive_key_matches_bac() {
+        let seed = [0x23; 16];
+        assert_eq!(
+            derive_key(&seed, ENC_COUNTER, Cipher::TripleDes),
+            bac::derive_key(&seed, 1).to_vec()
+        );
+    }
+}
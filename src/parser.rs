@@ -1,277 +1,863 @@
+use crate::check_digit::check_digit_of;
+use crate::country::CountryCode;
 use crate::document::*;
-use crate::error::Error;
+use crate::document_ref::{self, DocumentRef};
+use crate::error::{Error, ErrorKind};
+use crate::input;
+use crate::options::{CenturyPolicy, ParseOptions};
+use crate::raw::RawMrz;
+use crate::validation::{DecodedFields, ValidationFailure, ValidationReport};
 use chrono::prelude::*;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::str;
+use std::borrow::Cow;
+use std::ops::Range;
 use std::str::FromStr;
 
 lazy_static! {
     static ref VALID_PASSPORT_MRZ: Regex = Regex::new(r"^[A-Z0-9<]{88}$").unwrap();
     static ref VALID_IDENTITY_CARD_MRZ: Regex = Regex::new(r"^[A-Z0-9<]{90}$").unwrap();
+    static ref VALID_TD2_MRZ: Regex = Regex::new(r"^[A-Z0-9<]{72}$").unwrap();
 }
 
-const DATE_FORMAT: &str = "%y%m%d";
-
 // Field specification from https://www.icao.int/publications/Documents/9303_p4_cons_en.pdf
 // and
 // Field specification from https://www.icao.int/publications/Documents/9303_p5_cons_en.pdf
+// and
+// Field specification from https://www.icao.int/publications/Documents/9303_p6_cons_en.pdf
+// and
+// Field specification from https://www.icao.int/publications/Documents/9303_p7_cons_en.pdf
 pub(crate) fn parse(data: &str, check: bool) -> Result<Document, Error> {
+    let options = ParseOptions {
+        check,
+        ..ParseOptions::default()
+    };
+    parse_with_options(data, &options)
+}
+
+pub(crate) fn parse_with_options(data: &str, options: &ParseOptions) -> Result<Document, Error> {
+    let mut checks = if options.check {
+        Checks::Strict
+    } else {
+        Checks::Skip
+    };
+    parse_with_checks(data, &mut checks, options)
+}
+
+pub(crate) fn parse_ref(data: &str, check: bool) -> Result<DocumentRef<'_>, Error> {
+    let options = ParseOptions {
+        check,
+        ..ParseOptions::default()
+    };
+    parse_ref_with_options(data, &options)
+}
+
+pub(crate) fn parse_ref_with_options<'a>(
+    data: &'a str,
+    options: &ParseOptions,
+) -> Result<DocumentRef<'a>, Error> {
+    let mut checks = if options.check {
+        Checks::Strict
+    } else {
+        Checks::Skip
+    };
+    parse_ref_with_checks(data.trim(), &mut checks, options)
+}
+
+// Parses recording every failure instead of stopping at the first one, decoding as
+// many fields as possible
+pub(crate) fn validate(data: &str) -> ValidationReport {
+    let mut checks = Checks::Collect {
+        verified: 0,
+        failures: Vec::new(),
+        undecoded: None,
+    };
+    let decoded = input::normalize(data).and_then(|data| {
+        let fields = parse_fields(&data, &mut checks, &ParseOptions::default())?;
+        Ok((
+            fields.document().map(|document| document.to_document()),
+            fields.decoded(),
+        ))
+    });
+
+    let (verified, failures, undecoded) = match checks {
+        Checks::Collect {
+            verified,
+            failures,
+            undecoded,
+        } => (verified, failures, undecoded),
+        _ => unreachable!(),
+    };
+
+    match decoded {
+        Ok((document, fields)) => ValidationReport {
+            document: document.ok_or_else(|| undecoded.expect("undecoded field without error")),
+            fields: Some(fields),
+            verified,
+            failures,
+        },
+        Err(error) => ValidationReport {
+            document: Err(error),
+            fields: None,
+            verified,
+            failures,
+        },
+    }
+}
+
+fn parse_with_checks(
+    data: &str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Document, Error> {
+    let data = input::normalize(data)?;
+    let mut document = parse_ref_with_checks(&data, checks, options)?.to_document();
+
+    if options.keep_raw {
+        let raw = Some(RawMrz::new(&data, document.format()));
+        match &mut document {
+            Document::Passport(passport) => passport.raw = raw,
+            Document::IdentityCard(identity_card) => identity_card.raw = raw,
+            Document::Visa(visa) => visa.raw = raw,
+        }
+    }
+
+    Ok(document)
+}
+
+fn parse_ref_with_checks<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<DocumentRef<'a>, Error> {
+    let fields = parse_fields(data, checks, options)?;
+
+    // Fields are only left undecoded when collecting failures
+    Ok(fields.document().expect("undecoded field"))
+}
+
+fn parse_fields<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Fields<'a>, Error> {
+    match format_of(data) {
+        Some(Format::Td1) => parse_identity_card(data, checks, options),
+        Some(Format::Td2) => parse_td2_identity_card(data, checks, options),
+        Some(Format::Td3) => parse_passport(data, checks, options),
+        Some(format @ Format::MrvA) | Some(format @ Format::MrvB) => {
+            parse_visa(data, checks, options, format)
+        }
+        None => match data.len() {
+            72 | 88 | 90 => {
+                let offset = data
+                    .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '<'))
+                    .unwrap_or(0);
+                Err(Error::at(ErrorKind::InvalidChar, None, data, offset))
+            }
+            _ => Err(ErrorKind::InvalidFormat.into()),
+        },
+    }
+}
+
+// Determine the format of a concatenated MRZ from its length and, for two-line
+// formats, whether it is a visa.
+pub(crate) fn format_of(data: &str) -> Option<Format> {
+    let visa = data.starts_with('V');
+
     if VALID_PASSPORT_MRZ.is_match(data) {
-        parse_passport(data, check)
+        Some(if visa { Format::MrvA } else { Format::Td3 })
     } else if VALID_IDENTITY_CARD_MRZ.is_match(data) {
-        parse_identity_card(data, check)
+        Some(Format::Td1)
+    } else if VALID_TD2_MRZ.is_match(data) {
+        Some(if visa { Format::MrvB } else { Format::Td2 })
     } else {
-        Err(Error::InvalidFormat)
+        None
     }
 }
 
-fn char_to_num(full_str: &str, ind: usize) -> Result<u32, Error> {
-    let c = full_str.chars().nth(ind).ok_or(Error::InvalidFormat)?;
+// How check digits and field values are handled while parsing: skipped, failing on
+// the first mismatch, or counting matches and recording every failure for a validation
+// report, along with the first field that could not be decoded.
+enum Checks {
+    Skip,
+    Strict,
+    Collect {
+        verified: usize,
+        failures: Vec<ValidationFailure>,
+        undecoded: Option<Error>,
+    },
+}
 
-    if c.is_ascii_digit() {
-        Ok(c.to_digit(10).ok_or(Error::ExpectedDigit)?)
-    } else {
-        Err(Error::ExpectedDigit)
-    }
-}
-
-// Check digit calculation from https://www.icao.int/publications/Documents/9303_p3_cons_en.pdf (section 4.9)
-fn verify_check_digit(slice: &str, check_digit: u32) -> Result<(), Error> {
-    let mut weighting_iter = [7, 3, 1].iter().cycle();
-
-    let mut next = || weighting_iter.next().expect("cycle iter stopped");
-
-    let char_weighting = |c: char| -> Result<u32, Error> {
-        let num = match c {
-            '0' => 0,
-            '1' => 1,
-            '2' => 2,
-            '3' => 3,
-            '4' => 4,
-            '5' => 5,
-            '6' => 6,
-            '7' => 7,
-            '8' => 8,
-            '9' => 9,
-            'A' => 10,
-            'B' => 11,
-            'C' => 12,
-            'D' => 13,
-            'E' => 14,
-            'F' => 15,
-            'G' => 16,
-            'H' => 17,
-            'I' => 18,
-            'J' => 19,
-            'K' => 20,
-            'L' => 21,
-            'M' => 22,
-            'N' => 23,
-            'O' => 24,
-            'P' => 25,
-            'Q' => 26,
-            'R' => 27,
-            'S' => 28,
-            'T' => 29,
-            'U' => 30,
-            'V' => 31,
-            'W' => 32,
-            'X' => 33,
-            'Y' => 34,
-            'Z' => 35,
-            '<' => 0,
-            _ => return Err(Error::InvalidChar),
-        };
+impl Checks {
+    // Handles a field that is invalid but can still be decoded: an error when strict,
+    // a failure when collecting, and ignored when skipping
+    fn reject(&mut self, error: Error, range: Range<usize>) -> Result<(), Error> {
+        match self {
+            Checks::Skip => Ok(()),
+            Checks::Strict => Err(error),
+            Checks::Collect { failures, .. } => {
+                failures.push(ValidationFailure::from_error(&error, range));
+                Ok(())
+            }
+        }
+    }
 
-        Ok(num * next())
-    };
+    // Handles the result of decoding a field. When collecting, an error is recorded
+    // and the field left out so that the remaining fields can still be decoded.
+    fn decode<T>(
+        &mut self,
+        range: Range<usize>,
+        result: Result<T, Error>,
+    ) -> Result<Option<T>, Error> {
+        match (self, result) {
+            (_, Ok(value)) => Ok(Some(value)),
+            (
+                Checks::Collect {
+                    failures,
+                    undecoded,
+                    ..
+                },
+                Err(error),
+            ) => {
+                failures.push(ValidationFailure::from_error(&error, range));
+                undecoded.get_or_insert(error);
+                Ok(None)
+            }
+            (_, Err(error)) => Err(error),
+        }
+    }
 
-    let sum: u32 = slice
-        .chars()
-        .map(char_weighting)
-        .collect::<Result<Vec<_>, _>>()?
-        .iter()
-        .sum();
+    fn verify(
+        &mut self,
+        data: &str,
+        field: Field,
+        range: Range<usize>,
+        position: usize,
+    ) -> Result<(), Error> {
+        self.verify_ranges(data, field, std::slice::from_ref(&range), position)
+    }
 
-    let expected_check_digit = sum % 10;
+    // Verifies the check digit at `position` against the concatenation of `ranges`
+    fn verify_ranges(
+        &mut self,
+        data: &str,
+        field: Field,
+        ranges: &[Range<usize>],
+        position: usize,
+    ) -> Result<(), Error> {
+        if let Checks::Skip = self {
+            return Ok(());
+        }
 
-    if check_digit == expected_check_digit {
-        Ok(())
+        let chars = ranges.iter().flat_map(|range| {
+            data[range.clone()]
+                .char_indices()
+                .map(move |(index, c)| (range.start + index, c))
+        });
+        let expected = check_digit_of(chars)
+            .map_err(|index| Error::at(ErrorKind::InvalidChar, Some(field), data, index))?;
+        let found = data.as_bytes()[position] as char;
+
+        match self {
+            Checks::Skip => Ok(()),
+            Checks::Strict => match found.to_digit(10) {
+                None => Err(Error::at(
+                    ErrorKind::ExpectedDigit,
+                    Some(field),
+                    data,
+                    position,
+                )),
+                Some(digit) if digit != u32::from(expected) => Err(Error::at(
+                    ErrorKind::BadCheckDigit,
+                    Some(field),
+                    data,
+                    position,
+                )),
+                Some(_) => Ok(()),
+            },
+            Checks::Collect {
+                verified, failures, ..
+            } => {
+                if found.to_digit(10) == Some(u32::from(expected)) {
+                    *verified += 1;
+                } else {
+                    failures.push(ValidationFailure {
+                        kind: ErrorKind::BadCheckDigit,
+                        field,
+                        ranges: ranges.to_vec(),
+                        position,
+                        expected: Some(expected),
+                        found,
+                    });
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+// Fields decoded from an MRZ, those that could not be decoded left out when
+// collecting failures
+struct Fields<'a> {
+    format: Format,
+    document_code: Option<DocumentCode>,
+    country: Cow<'a, str>,
+    names: &'a str,
+    document_number: Cow<'a, str>,
+    nationality: Cow<'a, str>,
+    birth_date: Option<PartialDate>,
+    gender: Gender,
+    sex: char,
+    expiry_date: Option<NaiveDate>,
+    optional_data: &'a str,
+    optional_data_2: &'a str,
+}
+
+impl<'a> Fields<'a> {
+    // Document, if every field was decoded
+    fn document(&self) -> Option<DocumentRef<'a>> {
+        Some(DocumentRef {
+            format: self.format,
+            document_code: self.document_code?,
+            country: self.country.clone(),
+            names: self.names,
+            document_number: self.document_number.clone(),
+            nationality: self.nationality.clone(),
+            birth_date: self.birth_date?.latest_date()?,
+            birth_date_partial: self.birth_date?,
+            gender: self.gender.clone(),
+            sex: self.sex,
+            expiry_date: self.expiry_date?,
+            optional_data: self.optional_data,
+            optional_data_2: self.optional_data_2,
+        })
+    }
+
+    fn decoded(&self) -> DecodedFields {
+        DecodedFields {
+            format: self.format,
+            document_code: self.document_code,
+            country: self.country.to_string(),
+            surnames: document_ref::surnames(self.names)
+                .map(String::from)
+                .collect(),
+            given_names: document_ref::given_names(self.names)
+                .map(String::from)
+                .collect(),
+            document_number: self.document_number.to_string(),
+            nationality: self.nationality.to_string(),
+            birth_date: self.birth_date.and_then(|date| date.latest_date()),
+            birth_date_partial: self.birth_date,
+            gender: self.gender.clone(),
+            sex: self.sex,
+            expiry_date: self.expiry_date,
+            optional_data: self.optional_data.to_string(),
+            optional_data_2: self.optional_data_2.to_string(),
+        }
+    }
+}
+
+// Removes fillers, borrowing the field unless fillers appear before its end
+fn without_fillers(field: &str) -> Cow<'_, str> {
+    let field = field.trim_end_matches('<');
+
+    if field.contains('<') {
+        Cow::Owned(field.replace('<', ""))
     } else {
-        Err(Error::BadCheckDigit)
+        Cow::Borrowed(field)
     }
 }
 
-fn parse_passport(data: &str, check: bool) -> Result<Document, Error> {
-    let mrz = data.as_bytes();
+// Rejects codes missing from the ICAO registry when required by the options
+fn check_country_code(
+    data: &str,
+    range: Range<usize>,
+    kind: ErrorKind,
+    field: Field,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    if options.reject_unknown_country_codes && CountryCode::lookup(&data[range.clone()]).is_none() {
+        return Err(Error::at(kind, Some(field), data, range.start));
+    }
+
+    Ok(())
+}
 
-    if mrz[0] != b'P' {
-        return Err(Error::InvalidDocumentType);
+fn parse_issuing_state<'a>(
+    data: &'a str,
+    range: Range<usize>,
+    options: &ParseOptions,
+) -> Result<Cow<'a, str>, Error> {
+    check_country_code(
+        data,
+        range.clone(),
+        ErrorKind::InvalidIssuingState,
+        Field::IssuingState,
+        options,
+    )?;
+
+    Ok(without_fillers(&data[range]))
+}
+
+// Nationality codes are made up of letters, padded with fillers when shorter
+// than three characters (e.g. "D<<" for Germany), and must be listed by ICAO.
+fn parse_nationality<'a>(
+    data: &'a str,
+    range: Range<usize>,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Cow<'a, str>, Error> {
+    let field = &data[range.clone()];
+
+    let offset = match field.find(|c: char| !(c.is_ascii_uppercase() || c == '<')) {
+        Some(offset) => Some(offset),
+        None if CountryCode::lookup(field).is_none() => Some(0),
+        None => None,
+    };
+
+    if let Some(offset) = offset {
+        let error = Error::at(
+            ErrorKind::InvalidNationality,
+            Some(Field::Nationality),
+            data,
+            range.start + offset,
+        );
+
+        match checks {
+            // Rejected even when not checking if required by the options
+            Checks::Skip if options.reject_unknown_country_codes => return Err(error),
+            _ => checks.reject(error, range)?,
+        }
     }
 
-    let country = str::from_utf8(&mrz[2..5]).unwrap().replace('<', "");
-    let mut names = str::from_utf8(&mrz[5..43])
-        .unwrap()
-        .split("<<")
-        .collect::<Vec<_>>();
+    Ok(without_fillers(field))
+}
 
-    names.reverse();
+// Sex is `M`, `F`, or `X`/`<` when unspecified. Other characters are only accepted
+// (as `Gender::Other`) when not checking.
+fn parse_sex(data: &str, position: usize, checks: &mut Checks) -> Result<(Gender, char), Error> {
+    let sex = data.as_bytes()[position] as char;
+
+    let gender = match sex {
+        'M' => Gender::Male,
+        'F' => Gender::Female,
+        'X' | '<' => Gender::Unspecified,
+        _ => {
+            let error = Error::at(ErrorKind::InvalidSex, Some(Field::Sex), data, position);
+            checks.reject(error, position..position + 1)?;
+            Gender::Other
+        }
+    };
 
-    let surnames = names
-        .pop()
-        .ok_or(Error::InvalidFormat)?
-        .split('<')
-        .filter(|name| !name.is_empty())
-        .map(String::from)
-        .collect::<Vec<_>>();
+    Ok((gender, sex))
+}
+
+fn parse_document_code(data: &str, allowed: &[u8]) -> Result<DocumentCode, Error> {
+    let error = || {
+        Error::at(
+            ErrorKind::InvalidDocumentType,
+            Some(Field::DocumentCode),
+            data,
+            0,
+        )
+    };
 
-    let given_names = names
-        .pop()
-        .ok_or(Error::InvalidFormat)?
-        .split('<')
-        .filter(|name| !name.is_empty())
-        .map(String::from)
-        .collect::<Vec<_>>();
+    if !allowed.contains(&data.as_bytes()[0]) {
+        return Err(error());
+    }
+
+    data[0..2].parse().map_err(|_| error())
+}
 
-    let passport_number = str::from_utf8(&mrz[44..53]).unwrap().replace('<', "");
-    if check {
-        verify_check_digit(&data[44..53], char_to_num(data, 53)?)?;
+// Resolves the century of a YYMMDD date according to `policy`. Errors point at the
+// first non-digit, otherwise at the month or day out of range.
+fn parse_date(
+    data: &str,
+    range: Range<usize>,
+    kind: ErrorKind,
+    field: Field,
+    policy: CenturyPolicy,
+    options: &ParseOptions,
+) -> Result<NaiveDate, Error> {
+    let start = range.start;
+    let error = |offset| Error::at(kind, Some(field), data, start + offset);
+
+    if let Some(offset) = data[range.clone()].find(|c: char| !c.is_ascii_digit()) {
+        return Err(error(offset));
     }
 
-    let nationality = str::from_utf8(&mrz[54..57]).unwrap().replace('<', "");
-    let mut birth_date =
-        NaiveDate::parse_from_str(str::from_utf8(&mrz[57..63]).unwrap(), DATE_FORMAT)
-            .map_err(|_| Error::InvalidBirthDate)?;
+    parse_partial_date(data, range, kind, field, policy, options)?
+        .to_naive_date()
+        .ok_or_else(|| error(4))
+}
 
-    let birth_year = birth_date.year();
-    let current_year = Utc::now().year();
+// Unknown parts of a date are filled with `<`: either the day (YYMM<<) or both the
+// month and day (YY<<<<).
+fn parse_partial_date(
+    data: &str,
+    range: Range<usize>,
+    kind: ErrorKind,
+    field: Field,
+    policy: CenturyPolicy,
+    options: &ParseOptions,
+) -> Result<PartialDate, Error> {
+    let date = &data[range.clone()];
+    let error = |offset| Error::at(kind, Some(field), data, range.start + offset);
+
+    let known = match (&date[2..4], &date[4..6]) {
+        ("<<", "<<") => 2,
+        (_, "<<") => 4,
+        _ => 6,
+    };
 
-    if birth_year > current_year {
-        birth_date = birth_date.with_year(birth_year - 100).unwrap();
+    if let Some(offset) = date[..known].find(|c: char| !c.is_ascii_digit()) {
+        return Err(error(offset));
     }
 
-    if check {
-        verify_check_digit(&data[57..63], char_to_num(data, 63)?)?;
+    let component = |range: Range<usize>| {
+        if range.end <= known {
+            Some(date[range].parse::<u32>().unwrap())
+        } else {
+            None
+        }
+    };
+
+    let year = component(0..2).unwrap() as i32;
+    let month = component(2..4);
+    let day = component(4..6);
+
+    if matches!(month, Some(month) if !(1..=12).contains(&month)) {
+        return Err(error(2));
     }
 
-    let gender = match mrz[64] {
-        b'M' => Gender::Male,
-        b'F' => Gender::Female,
-        _ => Gender::Other,
+    // Unknown parts are taken as the start of the year or month
+    let (month_or_first, day_or_first) = (month.unwrap_or(1), day.unwrap_or(1));
+
+    let reference = options
+        .reference_date
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let reference = (reference.year(), reference.month(), reference.day());
+
+    let year = match policy {
+        CenturyPolicy::Past => {
+            let year = reference.0 - (reference.0 - year).rem_euclid(100);
+            if (year, month_or_first, day_or_first) > reference {
+                year - 100
+            } else {
+                year
+            }
+        }
+        CenturyPolicy::Window { years_before } => {
+            let start = (reference.0 - years_before as i32, reference.1, reference.2);
+            let year = start.0 + (year - start.0).rem_euclid(100);
+            if (year, month_or_first, day_or_first) < start {
+                year + 100
+            } else {
+                year
+            }
+        }
     };
 
-    let expiry_date = NaiveDate::parse_from_str(str::from_utf8(&mrz[65..71]).unwrap(), DATE_FORMAT)
-        .map_err(|_| Error::InvalidExpiryDate)?;
+    let date = PartialDate { year, month, day };
 
-    if check {
-        verify_check_digit(&data[65..71], char_to_num(data, 71)?)?;
+    if date.is_complete() && date.to_naive_date().is_none() {
+        return Err(error(4));
+    }
 
-        if !data[72..87].chars().all(|c| c == '<') {
-            verify_check_digit(&data[72..86], char_to_num(data, 86)?)?;
+    Ok(date)
+}
+
+fn parse_birth_date(
+    data: &str,
+    range: Range<usize>,
+    options: &ParseOptions,
+) -> Result<PartialDate, Error> {
+    parse_partial_date(
+        data,
+        range,
+        ErrorKind::InvalidBirthDate,
+        Field::BirthDate,
+        options.birth_date_policy,
+        options,
+    )
+}
+
+fn parse_expiry_date(
+    data: &str,
+    range: Range<usize>,
+    options: &ParseOptions,
+) -> Result<NaiveDate, Error> {
+    parse_date(
+        data,
+        range,
+        ErrorKind::InvalidExpiryDate,
+        Field::ExpiryDate,
+        options.expiry_date_policy,
+        options,
+    )
+}
+
+// Document numbers longer than nine characters continue in the optional data field,
+// signalled by a filler in place of the check digit. The remaining characters are
+// then followed by the check digit (calculated over the full number) and a filler.
+// Returns the length of the continuation including its check digit, if any.
+pub(crate) fn document_number_extension(
+    data: &str,
+    number: Range<usize>,
+    optional: Range<usize>,
+) -> Option<usize> {
+    if data.as_bytes()[number.end] != b'<' {
+        return None;
+    }
+
+    let optional_data = &data[optional];
+    Some(optional_data.find('<').unwrap_or(optional_data.len()))
+}
+
+// Returns the document number and what is left of the optional data.
+fn parse_document_number<'a>(
+    data: &'a str,
+    number: Range<usize>,
+    optional: Range<usize>,
+    checks: &mut Checks,
+) -> Result<(Cow<'a, str>, &'a str), Error> {
+    let check_digit_index = number.end;
+
+    let extension_length = match document_number_extension(data, number.clone(), optional.clone()) {
+        Some(extension_length) => extension_length,
+        None => {
+            checks.verify(
+                data,
+                Field::DocumentNumber,
+                number.clone(),
+                check_digit_index,
+            )?;
+
+            let document_number = without_fillers(&data[number]);
+            let optional_data = data[optional].trim_end_matches('<');
+            return Ok((document_number, optional_data));
         }
+    };
+
+    let optional_data = &data[optional.clone()];
 
-        let comp_check_digit_str = format!("{}{}{}", &data[44..54], &data[57..64], &data[65..87]);
-        verify_check_digit(&comp_check_digit_str, char_to_num(data, 87)?)?;
+    if extension_length < 2 {
+        return Err(Error::at(
+            ErrorKind::InvalidFormat,
+            Some(Field::DocumentNumber),
+            data,
+            optional.start,
+        ));
     }
 
-    Ok(Document::Passport(Passport {
+    let document_number = Cow::Owned(format!(
+        "{}{}",
+        without_fillers(&data[number.clone()]),
+        &optional_data[..extension_length - 1]
+    ));
+
+    checks.verify_ranges(
+        data,
+        Field::DocumentNumber,
+        &[
+            number,
+            optional.start..optional.start + extension_length - 1,
+        ],
+        optional.start + extension_length - 1,
+    )?;
+
+    let optional_data = optional_data
+        .get(extension_length + 1..)
+        .unwrap_or_default()
+        .trim_end_matches('<');
+
+    Ok((document_number, optional_data))
+}
+
+fn parse_passport<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Fields<'a>, Error> {
+    let document_code = checks.decode(0..2, parse_document_code(data, b"P"))?;
+
+    let country = parse_issuing_state(data, 2..5, options)?;
+    let names = &data[5..44];
+
+    let passport_number = without_fillers(&data[44..53]);
+    checks.verify(data, Field::DocumentNumber, 44..53, 53)?;
+
+    let nationality = parse_nationality(data, 54..57, checks, options)?;
+    let birth_date = checks.decode(57..63, parse_birth_date(data, 57..63, options))?;
+
+    checks.verify(data, Field::BirthDate, 57..63, 63)?;
+
+    let (gender, sex) = parse_sex(data, 64, checks)?;
+
+    let expiry_date = checks.decode(65..71, parse_expiry_date(data, 65..71, options))?;
+
+    checks.verify(data, Field::ExpiryDate, 65..71, 71)?;
+
+    if !data[72..87].chars().all(|c| c == '<') {
+        checks.verify(data, Field::PersonalNumber, 72..86, 86)?;
+    }
+
+    checks.verify_ranges(data, Field::Composite, &[44..54, 57..64, 65..87], 87)?;
+
+    let personal_number = data[72..86].trim_end_matches('<');
+
+    Ok(Fields {
+        format: Format::Td3,
+        document_code,
         country,
-        surnames,
-        given_names,
-        passport_number,
+        names,
+        document_number: passport_number,
         nationality,
         birth_date,
         gender,
+        sex,
         expiry_date,
-    }))
+        optional_data: personal_number,
+        optional_data_2: "",
+    })
 }
 
-fn parse_identity_card(data: &str, check: bool) -> Result<Document, Error> {
-    let mrz = data.as_bytes();
+fn parse_identity_card<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Fields<'a>, Error> {
+    let document_code = checks.decode(0..2, parse_document_code(data, b"IAC"))?;
 
-    if (mrz[0] != b'I') && (mrz[0] != b'A') && (mrz[0] != b'C') {
-        return Err(Error::InvalidDocumentType);
-    }
+    let country = parse_issuing_state(data, 2..5, options)?;
 
-    let country = str::from_utf8(&mrz[2..5]).unwrap().replace('<', "");
+    let names = &data[60..];
 
-    let mut names = str::from_utf8(&mrz[60..])
-        .unwrap()
-        .split("<<")
-        .collect::<Vec<_>>();
+    let (document_number, optional_data) = parse_document_number(data, 5..14, 15..30, checks)?;
 
-    names.reverse();
+    let nationality = parse_nationality(data, 45..48, checks, options)?;
+    let birth_date = checks.decode(30..36, parse_birth_date(data, 30..36, options))?;
 
-    let surnames = names
-        .pop()
-        .ok_or(Error::InvalidFormat)?
-        .split('<')
-        .filter(|name| !name.is_empty())
-        .map(String::from)
-        .collect::<Vec<_>>();
+    checks.verify(data, Field::BirthDate, 30..36, 36)?;
 
-    let given_names = names
-        .pop()
-        .ok_or(Error::InvalidFormat)?
-        .split('<')
-        .filter(|name| !name.is_empty())
-        .map(String::from)
-        .collect::<Vec<_>>();
+    let (gender, sex) = parse_sex(data, 37, checks)?;
 
-    let document_number = str::from_utf8(&mrz[5..14]).unwrap().replace('<', "");
-    if check {
-        verify_check_digit(&data[5..14], char_to_num(data, 14)?)?;
-    }
+    let expiry_date = checks.decode(38..44, parse_expiry_date(data, 38..44, options))?;
 
-    let nationality = str::from_utf8(&mrz[2..5]).unwrap().replace('<', "");
-    let mut birth_date =
-        NaiveDate::parse_from_str(str::from_utf8(&mrz[30..36]).unwrap(), DATE_FORMAT)
-            .map_err(|_| Error::InvalidBirthDate)?;
+    checks.verify(data, Field::ExpiryDate, 38..44, 44)?;
+    checks.verify_ranges(data, Field::Composite, &[5..30, 30..37, 38..45, 48..59], 59)?;
 
-    let birth_year = birth_date.year();
-    let current_year = Utc::now().year();
+    let optional_data_2 = data[48..59].trim_end_matches('<');
 
-    if birth_year > current_year {
-        birth_date = birth_date.with_year(birth_year - 100).unwrap();
-    }
+    Ok(Fields {
+        format: Format::Td1,
+        document_code,
+        country,
+        names,
+        document_number,
+        nationality,
+        birth_date,
+        gender,
+        sex,
+        expiry_date,
+        optional_data,
+        optional_data_2,
+    })
+}
 
-    if check {
-        verify_check_digit(&data[30..36], char_to_num(data, 36)?)?;
-    }
+fn parse_td2_identity_card<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+) -> Result<Fields<'a>, Error> {
+    let document_code = checks.decode(0..2, parse_document_code(data, b"IAC"))?;
 
-    let gender = match mrz[37] {
-        b'M' => Gender::Male,
-        b'F' => Gender::Female,
-        _ => Gender::Other,
-    };
+    let country = parse_issuing_state(data, 2..5, options)?;
+    let names = &data[5..36];
 
-    let expiry_date = NaiveDate::parse_from_str(str::from_utf8(&mrz[38..44]).unwrap(), DATE_FORMAT)
-        .map_err(|_| Error::InvalidExpiryDate)?;
+    let (document_number, optional_data) = parse_document_number(data, 36..45, 64..71, checks)?;
 
-    if check {
-        verify_check_digit(&data[38..44], char_to_num(data, 44)?)?;
+    let nationality = parse_nationality(data, 46..49, checks, options)?;
+    let birth_date = checks.decode(49..55, parse_birth_date(data, 49..55, options))?;
 
-        let comp_check_digit_str = format!(
-            "{}{}{}{}",
-            &data[5..30],
-            &data[30..37],
-            &data[38..45],
-            &data[48..59]
-        );
-        verify_check_digit(&comp_check_digit_str, char_to_num(data, 59)?)?;
-    }
+    checks.verify(data, Field::BirthDate, 49..55, 55)?;
+
+    let (gender, sex) = parse_sex(data, 56, checks)?;
+
+    let expiry_date = checks.decode(57..63, parse_expiry_date(data, 57..63, options))?;
+
+    checks.verify(data, Field::ExpiryDate, 57..63, 63)?;
+    checks.verify_ranges(data, Field::Composite, &[36..46, 49..56, 57..71], 71)?;
 
-    Ok(Document::IdentityCard(IdentityCard {
+    Ok(Fields {
+        format: Format::Td2,
+        document_code,
         country,
-        surnames,
-        given_names,
+        names,
         document_number,
         nationality,
         birth_date,
         gender,
+        sex,
         expiry_date,
-    }))
+        optional_data,
+        optional_data_2: "",
+    })
+}
+
+// MRV-A and MRV-B share the same fields, with the second line starting after the
+// (longer or shorter) first line. Visas have no composite check digit.
+fn parse_visa<'a>(
+    data: &'a str,
+    checks: &mut Checks,
+    options: &ParseOptions,
+    format: Format,
+) -> Result<Fields<'a>, Error> {
+    let l2 = format.line_length();
+
+    let document_code = checks.decode(0..2, parse_document_code(data, b"V"))?;
+
+    let country = parse_issuing_state(data, 2..5, options)?;
+    let names = &data[5..l2];
+
+    let visa_number = without_fillers(&data[l2..l2 + 9]);
+    checks.verify(data, Field::DocumentNumber, l2..l2 + 9, l2 + 9)?;
+
+    let nationality = parse_nationality(data, l2 + 10..l2 + 13, checks, options)?;
+    let birth_date = checks.decode(
+        l2 + 13..l2 + 19,
+        parse_birth_date(data, l2 + 13..l2 + 19, options),
+    )?;
+
+    checks.verify(data, Field::BirthDate, l2 + 13..l2 + 19, l2 + 19)?;
+
+    let (gender, sex) = parse_sex(data, l2 + 20, checks)?;
+
+    let expiry_date = checks.decode(
+        l2 + 21..l2 + 27,
+        parse_expiry_date(data, l2 + 21..l2 + 27, options),
+    )?;
+
+    checks.verify(data, Field::ExpiryDate, l2 + 21..l2 + 27, l2 + 27)?;
+
+    let optional_data = data[l2 + 28..].trim_end_matches('<');
+
+    Ok(Fields {
+        format,
+        document_code,
+        country,
+        names,
+        document_number: visa_number,
+        nationality,
+        birth_date,
+        gender,
+        sex,
+        expiry_date,
+        optional_data,
+        optional_data_2: "",
+    })
 }
 
 impl FromStr for Document {
@@ -297,6 +883,7 @@ mod tests {
                 assert_eq!(passport.given_names, vec!["SARAH"]);
                 assert_eq!(passport.passport_number, "ZE000509");
                 assert_eq!(passport.nationality, "CAN");
+                assert_eq!(passport.personal_number, "");
             }
             _ => panic!("unexpected document"),
         }
@@ -320,6 +907,7 @@ mod tests {
                 assert_eq!(passport.expiry_date.year(), 2012);
                 assert_eq!(passport.expiry_date.month(), 4);
                 assert_eq!(passport.expiry_date.day(), 15);
+                assert_eq!(passport.personal_number, "ZE184226B");
             }
             _ => panic!("unexpected document"),
         }
@@ -343,6 +931,24 @@ mod tests {
                 assert_eq!(passport.expiry_date.year(), 2012);
                 assert_eq!(passport.expiry_date.month(), 4);
                 assert_eq!(passport.expiry_date.day(), 15);
+                assert_eq!(passport.personal_number, "ZE184226B");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_passport_full_length_name() {
+        // The name field runs up to the last character of line 1
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<JOHANNA<ELISABETHA\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.surnames, vec!["ERIKSSON"]);
+                assert_eq!(
+                    passport.given_names,
+                    vec!["ANNA", "MARIA", "JOHANNA", "ELISABETHA"]
+                );
             }
             _ => panic!("unexpected document"),
         }
@@ -352,14 +958,14 @@ mod tests {
     fn parse_passport_invalid_length() {
         let mrz = "ABC<<";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidFormat);
+        assert_eq!(error.kind(), ErrorKind::InvalidFormat);
     }
 
     #[test]
     fn parse_passport_invalid_encoding() {
         let mrz = "🕶️";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidFormat);
+        assert_eq!(error.kind(), ErrorKind::InvalidFormat);
     }
 
     #[test]
@@ -367,7 +973,7 @@ mod tests {
         let mrz = "X<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
                    L898902C36UTO7408122F1204159ZE184226B<<<<<10";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidDocumentType);
+        assert_eq!(error.kind(), ErrorKind::InvalidDocumentType);
     }
 
     #[test]
@@ -375,7 +981,171 @@ mod tests {
         let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
                    L898902C36UTO7A08122F1204159ZE184226B<<<<<10";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidBirthDate);
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
+    }
+
+    #[test]
+    fn parse_passport_partial_birth_date() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408<<7F1204159ZE184226B<<<<<10";
+
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(
+                    passport.birth_date_partial,
+                    PartialDate {
+                        year: 1974,
+                        month: Some(8),
+                        day: None,
+                    }
+                );
+                assert!(!passport.birth_date_partial.is_complete());
+                assert_eq!(passport.birth_date_partial.to_naive_date(), None);
+                assert_eq!(
+                    passport.birth_date,
+                    NaiveDate::from_ymd_opt(1974, 8, 31).unwrap()
+                );
+            }
+            _ => panic!("unexpected document"),
+        }
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO74<<<<1F1204159ZE184226B<<<<<18";
+
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.birth_date_partial.year, 1974);
+                assert_eq!(passport.birth_date_partial.month, None);
+                assert_eq!(passport.birth_date_partial.day, None);
+                assert_eq!(
+                    passport.birth_date,
+                    NaiveDate::from_ymd_opt(1974, 12, 31).unwrap()
+                );
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_passport_invalid_partial_birth_date() {
+        // Unknown month with a known day
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO74<<122F1204159ZE184226B<<<<<10";
+        let error = parse(mrz, false).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
+        assert_eq!(error.location().unwrap().offset, 59);
+
+        // Unknown year
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO<<<<<<0F1204159ZE184226B<<<<<10";
+        let error = parse(mrz, false).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
+        assert_eq!(error.location().unwrap().offset, 57);
+    }
+
+    #[test]
+    fn parse_passport_unspecified_sex() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122X1204159ZE184226B<<<<<10";
+
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.gender, Gender::Unspecified);
+                assert_eq!(passport.sex, 'X');
+            }
+            _ => panic!("unexpected document"),
+        }
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122<1204159ZE184226B<<<<<10";
+
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.gender, Gender::Unspecified);
+                assert_eq!(passport.sex, '<');
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_passport_invalid_sex() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122Z1204159ZE184226B<<<<<10";
+
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidSex);
+        assert_eq!(error.field(), Some(Field::Sex));
+        assert_eq!(error.location().unwrap().offset, 64);
+
+        match parse(mrz, false).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.gender, Gender::Other);
+                assert_eq!(passport.sex, 'Z');
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_document_code() {
+        let mrz = "PDUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+        let document = parse(mrz, true).unwrap();
+        assert_eq!(document.document_code().as_str(), "PD");
+        assert_eq!(
+            document.document_code().subtype(),
+            Some(DocumentSubtype::Diplomatic)
+        );
+
+        let mrz = "IRNLDSPECI20212999999990<<<<<<\
+                   6503101F3108022NLDA1B2C3<<<<<9\
+                   DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.document_code.document_type(), 'I');
+                assert_eq!(
+                    identity_card.document_code.subtype(),
+                    Some(DocumentSubtype::ResidencePermit)
+                );
+            }
+            _ => panic!("unexpected document"),
+        }
+
+        let mrz = "C<ITACA00000AA4<<<<<<<<<<<<<<<\
+                   6412308F2212304ITA<<<<<<<<<<<0\
+                   ROSSI<<BIANCA<<<<<<<<<<<<<<<<<";
+        let document = parse(mrz, true).unwrap();
+        assert_eq!(document.document_code().as_str(), "C<");
+        assert_eq!(document.document_code().subtype(), None);
+    }
+
+    #[test]
+    fn parse_keep_raw() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+        assert_eq!(parse(mrz, true).unwrap().raw(), None);
+
+        let options = ParseOptions {
+            keep_raw: true,
+            ..ParseOptions::default()
+        };
+        let document = parse_with_options(mrz, &options).unwrap();
+        assert_eq!(document, parse(mrz, true).unwrap());
+        let raw = document.raw().unwrap();
+
+        assert_eq!(raw.lines[0], "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<");
+        assert_eq!(raw.lines[1], "L898902C36UTO7408122F1204159ZE184226B<<<<<10");
+        assert_eq!(raw.spans.len(), 14);
+
+        let personal_number = raw.field(Field::PersonalNumber).collect::<Vec<_>>();
+        assert_eq!(personal_number[0].range, 72..86);
+        assert_eq!(personal_number[0].columns, 28..42);
+        assert_eq!(raw.text(personal_number[0]), "ZE184226B<<<<<");
+        assert_eq!(raw.text(personal_number[1]), "1");
     }
 
     #[test]
@@ -383,7 +1153,33 @@ mod tests {
         let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
                    L898902C36UTO7408122F1<0A159ZE184226B<<<<<10";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidExpiryDate);
+        assert_eq!(error.kind(), ErrorKind::InvalidExpiryDate);
+    }
+
+    #[test]
+    fn parse_passport_unknown_country_codes() {
+        let options = ParseOptions {
+            reject_unknown_country_codes: true,
+            ..ParseOptions::default()
+        };
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        parse_with_options(mrz, &options).unwrap();
+
+        let mrz = "P<QQQERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        parse(mrz, true).unwrap();
+        let error = parse_with_options(mrz, &options).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidIssuingState);
+        assert_eq!(error.field(), Some(Field::IssuingState));
+        assert_eq!(error.location().unwrap().offset, 2);
+
+        let mrz = "P<D<<ERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36QQQ7408122F1204159ZE184226B<<<<<10";
+        let error = parse_with_options(mrz, &options).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidNationality);
+        assert_eq!(error.location().unwrap().offset, 54);
     }
 
     #[test]
@@ -392,7 +1188,7 @@ mod tests {
                    L898902C36UTO7408122F1204159ZE184226B<<<<<11";
         parse(mrz, false).unwrap();
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::BadCheckDigit);
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
     }
 
     #[test]
@@ -404,6 +1200,27 @@ mod tests {
         invalid_mrz.parse::<Document>().unwrap_err();
     }
 
+    #[test]
+    fn parse_passport_multiple_lines() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\r\n\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10  \n";
+        match parse(mrz, true).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.passport_number, "L898902C3");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_passport_invalid_line_length() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<1";
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidLineLength);
+        assert_eq!(error.location().unwrap().line, 1);
+    }
+
     #[test]
     fn parse_identity_card() {
         let mrz = "C<ITACA00000AA4<<<<<<<<<<<<<<<\
@@ -424,6 +1241,8 @@ mod tests {
                 assert_eq!(identity_card.expiry_date.year(), 2022);
                 assert_eq!(identity_card.expiry_date.month(), 12);
                 assert_eq!(identity_card.expiry_date.day(), 30);
+                assert_eq!(identity_card.optional_data, "");
+                assert_eq!(identity_card.optional_data_2, "");
             }
             _ => panic!("unexpected document"),
         }
@@ -454,18 +1273,139 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_identity_card_optional_data() {
+        let mrz = "I<NLDSPECI20212999999990<<<<<<\
+        6503101F3108022NLDA1B2C3<<<<<9\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.document_number, "SPECI2021");
+                assert_eq!(identity_card.optional_data, "999999990");
+                assert_eq!(identity_card.optional_data_2, "A1B2C3");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_card_long_document_number() {
+        let mrz = "I<UTOD23145890<7349<<<<<<<<<<<\
+        3407127M9507122UTO<<<<<<<<<<<2\
+        STEVENSON<<PETER<JOHN<<<<<<<<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.document_number, "D23145890734");
+                assert_eq!(identity_card.optional_data, "");
+                assert_eq!(identity_card.surnames, vec!["STEVENSON"]);
+                assert_eq!(identity_card.given_names, vec!["PETER", "JOHN"]);
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_card_long_document_number_with_optional_data() {
+        let mrz = "I<UTOD23145890<7349<ABC123<<<<\
+        3407127M9507122UTO<<<<<<<<<<<3\
+        STEVENSON<<PETER<JOHN<<<<<<<<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.document_number, "D23145890734");
+                assert_eq!(identity_card.optional_data, "ABC123");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_card_long_document_number_invalid_check_digit() {
+        let mrz = "I<UTOD23145890<7348<<<<<<<<<<<\
+        3407127M9507122UTO<<<<<<<<<<<9\
+        STEVENSON<<PETER<JOHN<<<<<<<<<";
+
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+    }
+
+    #[test]
+    fn parse_identity_card_nationality_differs_from_country() {
+        let mrz = "C<ITACA00000AA4<<<<<<<<<<<<<<<\
+        6412308F2212304ROU<<<<<<<<<<<0\
+        ROSSI<<BIANCA<<<<<<<<<<<<<<<<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.country, "ITA");
+                assert_eq!(identity_card.nationality, "ROU");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_card_residence_permit_nationality() {
+        let mrz = "IRNLDSPECI20212<<<<<<<<<<<<<<<\
+        6503101F3108022SYR<<<<<<<<<<<8\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.country, "NLD");
+                assert_eq!(identity_card.nationality, "SYR");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_card_invalid_nationality() {
+        let mrz = "I<NLDSPECI20212<<<<<<<<<<<<<<<\
+        6503101F3108022N1D<<<<<<<<<<<8\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        parse(mrz, false).unwrap();
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidNationality);
+    }
+
+    #[test]
+    fn parse_identity_card_unknown_nationality() {
+        let mrz = "I<NLDSPECI20212<<<<<<<<<<<<<<<\
+        6503101F3108022QQQ<<<<<<<<<<<8\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        parse(mrz, false).unwrap();
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidNationality);
+        assert_eq!(error.location().unwrap().offset, 45);
+
+        // Codes shorter than three letters are padded with fillers
+        let mrz = "I<NLDSPECI20212<<<<<<<<<<<<<<<\
+        6503101F3108022D<<<<<<<<<<<<<8\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => assert_eq!(identity_card.nationality, "D"),
+            _ => panic!("unexpected document"),
+        }
+    }
+
     #[test]
     fn parse_identity_card_invalid_length() {
         let mrz = "I<<<";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidFormat);
+        assert_eq!(error.kind(), ErrorKind::InvalidFormat);
     }
 
     #[test]
     fn parse_identity_card_invalid_encoding() {
         let mrz = "👺";
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidFormat);
+        assert_eq!(error.kind(), ErrorKind::InvalidFormat);
     }
 
     #[test]
@@ -475,7 +1415,7 @@ mod tests {
         DE<BRUIJN<<WILLEKE<LISELOTTE<<";
 
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidDocumentType);
+        assert_eq!(error.kind(), ErrorKind::InvalidDocumentType);
     }
 
     #[test]
@@ -485,7 +1425,7 @@ mod tests {
         DE<BRUIJN<<WILLEKE<LISELOTTE<<";
 
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidBirthDate);
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
     }
 
     #[test]
@@ -495,7 +1435,7 @@ mod tests {
         DE<BRUIJN<<WILLEKE<LISELOTTE<<";
 
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::InvalidExpiryDate);
+        assert_eq!(error.kind(), ErrorKind::InvalidExpiryDate);
     }
 
     #[test]
@@ -505,6 +1445,236 @@ mod tests {
         DE<BRUIJN<<WILLEKE<LISELOTTE<<";
 
         let error = parse(mrz, true).unwrap_err();
-        assert_eq!(error, Error::BadCheckDigit);
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+    }
+
+    #[test]
+    fn parse_td2_identity_card() {
+        let mrz = "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+        D231458907UTO7408122F1204159<<<<<<<6";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.format, Format::Td2);
+                assert_eq!(identity_card.country, "UTO");
+                assert_eq!(identity_card.surnames, vec!["ERIKSSON"]);
+                assert_eq!(identity_card.given_names, vec!["ANNA", "MARIA"]);
+                assert_eq!(identity_card.document_number, "D23145890");
+                assert_eq!(identity_card.nationality, "UTO");
+                assert_eq!(identity_card.birth_date.year(), 1974);
+                assert_eq!(identity_card.birth_date.month(), 8);
+                assert_eq!(identity_card.birth_date.day(), 12);
+                assert_eq!(identity_card.gender, Gender::Female);
+                assert_eq!(identity_card.expiry_date.year(), 2012);
+                assert_eq!(identity_card.expiry_date.month(), 4);
+                assert_eq!(identity_card.expiry_date.day(), 15);
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_td2_identity_card_multiple_names() {
+        let mrz = "C<FRADUPONT<MOREAU<<MARIE<CLAIRE<<<<\
+        X4RTBPFW46BEL8805142F300101912345670";
+
+        match parse(mrz, true).unwrap() {
+            Document::IdentityCard(identity_card) => {
+                assert_eq!(identity_card.format, Format::Td2);
+                assert_eq!(identity_card.country, "FRA");
+                assert_eq!(identity_card.surnames, vec!["DUPONT", "MOREAU"]);
+                assert_eq!(identity_card.given_names, vec!["MARIE", "CLAIRE"]);
+                assert_eq!(identity_card.document_number, "X4RTBPFW4");
+                assert_eq!(identity_card.nationality, "BEL");
+                assert_eq!(identity_card.expiry_date.year(), 2030);
+                assert_eq!(identity_card.optional_data, "1234567");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_td2_identity_card_invalid_document_type() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+        D231458907UTO7408122F1204159<<<<<<<6";
+
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidDocumentType);
+    }
+
+    #[test]
+    fn parse_td2_identity_card_invalid_check_digit() {
+        let mrz = "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+        D231458907UTO7408122F1204159<<<<<<<7";
+
+        parse(mrz, false).unwrap();
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+    }
+
+    #[test]
+    fn parse_mrv_a_visa() {
+        let mrz = "VNUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L8988901C4XXX4009078F96121096ZE184226B<<<<<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::Visa(visa) => {
+                assert_eq!(visa.format, Format::MrvA);
+                assert_eq!(visa.country, "UTO");
+                assert_eq!(visa.surnames, vec!["ERIKSSON"]);
+                assert_eq!(visa.given_names, vec!["ANNA", "MARIA"]);
+                assert_eq!(visa.visa_number, "L8988901C");
+                assert_eq!(visa.nationality, "XXX");
+                assert_eq!(visa.birth_date.year(), 1940);
+                assert_eq!(visa.birth_date.month(), 9);
+                assert_eq!(visa.birth_date.day(), 7);
+                assert_eq!(visa.gender, Gender::Female);
+                assert_eq!(visa.expiry_date.year(), 1996);
+                assert_eq!(visa.expiry_date.month(), 12);
+                assert_eq!(visa.expiry_date.day(), 10);
+                assert_eq!(visa.optional_data, "6ZE184226B");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_mrv_b_visa() {
+        let mrz = "VNUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\
+                   L8988901C4XXX4009078F9612109<<<<<<<<";
+
+        match parse(mrz, true).unwrap() {
+            Document::Visa(visa) => {
+                assert_eq!(visa.format, Format::MrvB);
+                assert_eq!(visa.country, "UTO");
+                assert_eq!(visa.surnames, vec!["ERIKSSON"]);
+                assert_eq!(visa.given_names, vec!["ANNA", "MARIA"]);
+                assert_eq!(visa.visa_number, "L8988901C");
+                assert_eq!(visa.nationality, "XXX");
+                assert_eq!(visa.birth_date.year(), 1940);
+                assert_eq!(visa.gender, Gender::Female);
+                assert_eq!(visa.expiry_date.year(), 1996);
+                assert_eq!(visa.optional_data, "");
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn parse_visa_invalid_check_digit() {
+        let mrz = "VNUTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L8988901C4XXX4009078F96121086ZE184226B<<<<<<";
+
+        parse(mrz, false).unwrap();
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<11";
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::BadCheckDigit);
+        assert_eq!(error.field(), Some(Field::Composite));
+        assert_eq!(error.location().unwrap().line, 1);
+        assert_eq!(error.location().unwrap().column, 43);
+        assert_eq!(error.character(), Some('1'));
+
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7A08122F1204159ZE184226B<<<<<10";
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
+        assert_eq!(error.field(), Some(Field::BirthDate));
+        assert_eq!(error.location().unwrap().offset, 58);
+        assert_eq!(error.character(), Some('A'));
+
+        let mrz = "I<NLDSPECI20212<<<<<<<<<<<<<<<\
+        6513101F3108022NLD<<<<<<<<<<<8\
+        DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidBirthDate);
+        assert_eq!(error.location().unwrap().line, 1);
+        assert_eq!(error.location().unwrap().column, 2);
+    }
+
+    #[test]
+    fn parse_invalid_char_location() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226b<<<<<10";
+        let error = parse(mrz, true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidChar);
+        assert_eq!(error.location().unwrap().offset, 80);
+        assert_eq!(error.character(), Some('b'));
+        assert_eq!(
+            error.annotate(mrz).lines().nth(2).unwrap(),
+            format!("{}^", " ".repeat(36))
+        );
+    }
+
+    fn options(reference_date: (i32, u32, u32)) -> ParseOptions {
+        ParseOptions {
+            reference_date: NaiveDate::from_ymd_opt(
+                reference_date.0,
+                reference_date.1,
+                reference_date.2,
+            ),
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn parse_birth_date_century() {
+        let date = |data, reference| parse_birth_date(data, 0..6, &options(reference));
+
+        assert_eq!(date("740812", (2026, 10, 16)).unwrap().year, 1974);
+        assert_eq!(date("250101", (2026, 10, 16)).unwrap().year, 2025);
+        assert_eq!(date("261016", (2026, 10, 16)).unwrap().year, 2026);
+        assert_eq!(date("261017", (2026, 10, 16)).unwrap().year, 1926);
+        assert_eq!(date("250101", (2024, 6, 1)).unwrap().year, 1925);
+        assert_eq!(date("2610<<", (2026, 10, 16)).unwrap().year, 2026);
+        assert_eq!(date("2611<<", (2026, 10, 16)).unwrap().year, 1926);
+        assert_eq!(date("26<<<<", (2026, 10, 16)).unwrap().year, 2026);
+        assert_eq!(date("000229", (2026, 10, 16)).unwrap().year, 2000);
+        assert_eq!(
+            date("000229", (1999, 1, 1)).unwrap_err().kind(),
+            ErrorKind::InvalidBirthDate
+        );
+    }
+
+    #[test]
+    fn parse_expiry_date_century() {
+        let date = |data, reference| parse_expiry_date(data, 0..6, &options(reference));
+
+        assert_eq!(date("361016", (2026, 10, 16)).unwrap().year(), 2036);
+        assert_eq!(date("451231", (2026, 10, 16)).unwrap().year(), 2045);
+        assert_eq!(date("461016", (2026, 10, 16)).unwrap().year(), 1946);
+        assert_eq!(date("991231", (2026, 10, 16)).unwrap().year(), 1999);
+        assert_eq!(date("120415", (2026, 10, 16)).unwrap().year(), 2012);
+        assert_eq!(date("061015", (2026, 10, 16)).unwrap().year(), 2006);
+        assert_eq!(date("961210", (1995, 1, 1)).unwrap().year(), 1996);
+
+        let options = ParseOptions {
+            expiry_date_policy: CenturyPolicy::Window { years_before: 20 },
+            ..options((2026, 10, 16))
+        };
+        assert_eq!(
+            parse_expiry_date("991231", 0..6, &options).unwrap().year(),
+            2099
+        );
+    }
+
+    #[test]
+    fn parse_with_reference_date() {
+        let mrz = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+                   L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+        match parse_with_options(mrz, &options((1970, 1, 1))).unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.birth_date.year(), 1874);
+                assert_eq!(passport.expiry_date.year(), 1912);
+            }
+            _ => panic!("unexpected document"),
+        }
     }
 }
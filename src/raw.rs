@@ -0,0 +1,289 @@
+use crate::document::{Field, Format};
+use crate::parser::document_number_extension;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// MRZ as read, with the location of every field
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawMrz {
+    /// Lines of the MRZ
+    pub lines: Vec<String>,
+    /// Spans of every field and check digit, in order, covering the whole MRZ
+    pub spans: Vec<Span>,
+}
+
+/// Location of a field or check digit within the MRZ
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    /// Field the characters belong to
+    pub field: Field,
+    /// Whether the span is the check digit of the field rather than its value
+    pub check_digit: bool,
+    /// Position within the concatenated MRZ
+    pub range: Range<usize>,
+    /// Line of the MRZ (starting at 0)
+    pub line: usize,
+    /// Columns within the line (starting at 0)
+    pub columns: Range<usize>,
+}
+
+impl RawMrz {
+    pub(crate) fn new(mrz: &str, format: Format) -> RawMrz {
+        let line_length = format.line_length();
+
+        let lines = mrz
+            .as_bytes()
+            .chunks(line_length)
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect();
+
+        let spans = layout(mrz, format)
+            .into_iter()
+            .filter(|(_, _, range)| !range.is_empty())
+            .map(|(field, check_digit, range)| Span {
+                field,
+                check_digit,
+                line: range.start / line_length,
+                columns: range.start % line_length..(range.end - 1) % line_length + 1,
+                range,
+            })
+            .collect();
+
+        RawMrz { lines, spans }
+    }
+
+    /// Spans of a field, its value followed by its check digit if any
+    pub fn field(&self, field: Field) -> impl Iterator<Item = &Span> {
+        self.spans.iter().filter(move |span| span.field == field)
+    }
+
+    /// Characters of a span
+    pub fn text(&self, span: &Span) -> &str {
+        &self.lines[span.line][span.columns.clone()]
+    }
+}
+
+// Field layout of an MRZ, as (field, check digit, range on the concatenated MRZ)
+fn layout(mrz: &str, format: Format) -> Vec<(Field, bool, Range<usize>)> {
+    let mut layout = fixed_layout(format);
+
+    let (number, optional) = match format {
+        Format::Td1 => (5..14, 15..30),
+        Format::Td2 => (36..45, 64..71),
+        _ => return layout,
+    };
+
+    // Document numbers longer than nine characters continue in the optional data,
+    // with a filler in place of the check digit
+    if let Some(extension_length) = document_number_extension(mrz, number.clone(), optional.clone())
+    {
+        let check_digit = optional.start + extension_length - 1;
+
+        layout.retain(|(_, _, range)| *range != (number.end..number.end + 1));
+        for (field, _, range) in layout.iter_mut() {
+            if *range == number {
+                *range = number.start..number.end + 1;
+            } else if *field == Field::OptionalData && *range == optional {
+                *range = check_digit + 1..optional.end;
+            }
+        }
+
+        let index = layout
+            .iter()
+            .position(|(_, _, range)| range.start == check_digit + 1)
+            .expect("optional data in layout");
+        layout.splice(
+            index..index,
+            vec![
+                (Field::DocumentNumber, false, optional.start..check_digit),
+                (Field::DocumentNumber, true, check_digit..check_digit + 1),
+            ],
+        );
+    }
+
+    layout
+}
+
+// Field layout of each format when the document number fits in its field
+fn fixed_layout(format: Format) -> Vec<(Field, bool, Range<usize>)> {
+    use Field::*;
+
+    match format {
+        // https://www.icao.int/publications/Documents/9303_p5_cons_en.pdf
+        Format::Td1 => vec![
+            (DocumentCode, false, 0..2),
+            (IssuingState, false, 2..5),
+            (DocumentNumber, false, 5..14),
+            (DocumentNumber, true, 14..15),
+            (OptionalData, false, 15..30),
+            (BirthDate, false, 30..36),
+            (BirthDate, true, 36..37),
+            (Sex, false, 37..38),
+            (ExpiryDate, false, 38..44),
+            (ExpiryDate, true, 44..45),
+            (Nationality, false, 45..48),
+            (OptionalData, false, 48..59),
+            (Composite, true, 59..60),
+            (Name, false, 60..90),
+        ],
+        // https://www.icao.int/publications/Documents/9303_p6_cons_en.pdf
+        Format::Td2 => vec![
+            (DocumentCode, false, 0..2),
+            (IssuingState, false, 2..5),
+            (Name, false, 5..36),
+            (DocumentNumber, false, 36..45),
+            (DocumentNumber, true, 45..46),
+            (Nationality, false, 46..49),
+            (BirthDate, false, 49..55),
+            (BirthDate, true, 55..56),
+            (Sex, false, 56..57),
+            (ExpiryDate, false, 57..63),
+            (ExpiryDate, true, 63..64),
+            (OptionalData, false, 64..71),
+            (Composite, true, 71..72),
+        ],
+        // https://www.icao.int/publications/Documents/9303_p4_cons_en.pdf
+        Format::Td3 => vec![
+            (DocumentCode, false, 0..2),
+            (IssuingState, false, 2..5),
+            (Name, false, 5..44),
+            (DocumentNumber, false, 44..53),
+            (DocumentNumber, true, 53..54),
+            (Nationality, false, 54..57),
+            (BirthDate, false, 57..63),
+            (BirthDate, true, 63..64),
+            (Sex, false, 64..65),
+            (ExpiryDate, false, 65..71),
+            (ExpiryDate, true, 71..72),
+            (PersonalNumber, false, 72..86),
+            (PersonalNumber, true, 86..87),
+            (Composite, true, 87..88),
+        ],
+        // https://www.icao.int/publications/Documents/9303_p7_cons_en.pdf
+        Format::MrvA | Format::MrvB => {
+            let l2 = format.line_length();
+            vec![
+                (DocumentCode, false, 0..2),
+                (IssuingState, false, 2..5),
+                (Name, false, 5..l2),
+                (DocumentNumber, false, l2..l2 + 9),
+                (DocumentNumber, true, l2 + 9..l2 + 10),
+                (Nationality, false, l2 + 10..l2 + 13),
+                (BirthDate, false, l2 + 13..l2 + 19),
+                (BirthDate, true, l2 + 19..l2 + 20),
+                (Sex, false, l2 + 20..l2 + 21),
+                (ExpiryDate, false, l2 + 21..l2 + 27),
+                (ExpiryDate, true, l2 + 27..l2 + 28),
+                (OptionalData, false, l2 + 28..2 * l2),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_covers_mrz() {
+        for format in [
+            Format::Td1,
+            Format::Td2,
+            Format::Td3,
+            Format::MrvA,
+            Format::MrvB,
+        ] {
+            let layout = fixed_layout(format);
+            assert_eq!(layout[0].2.start, 0);
+            assert!(layout
+                .windows(2)
+                .all(|pair| pair[0].2.end == pair[1].2.start));
+            assert_eq!(
+                layout.last().unwrap().2.end,
+                format.lines() * format.line_length()
+            );
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let mrz = "I<NLDSPECI20212999999990<<<<<<\
+                   6503101F3108022NLDA1B2C3<<<<<9\
+                   DE<BRUIJN<<WILLEKE<LISELOTTE<<";
+        let raw = RawMrz::new(mrz, Format::Td1);
+
+        assert_eq!(
+            raw.lines,
+            vec![
+                "I<NLDSPECI20212999999990<<<<<<",
+                "6503101F3108022NLDA1B2C3<<<<<9",
+                "DE<BRUIJN<<WILLEKE<LISELOTTE<<",
+            ]
+        );
+
+        let birth_date = raw.field(Field::BirthDate).collect::<Vec<_>>();
+        assert_eq!(birth_date.len(), 2);
+        assert_eq!(birth_date[0].range, 30..36);
+        assert_eq!(birth_date[0].line, 1);
+        assert_eq!(birth_date[0].columns, 0..6);
+        assert_eq!(raw.text(birth_date[0]), "650310");
+        assert!(birth_date[1].check_digit);
+        assert_eq!(raw.text(birth_date[1]), "1");
+
+        let composite = raw.field(Field::Composite).next().unwrap();
+        assert_eq!(composite.columns, 29..30);
+        assert_eq!(raw.text(composite), "9");
+
+        let name = raw.field(Field::Name).next().unwrap();
+        assert_eq!(name.line, 2);
+        assert_eq!(raw.text(name), "DE<BRUIJN<<WILLEKE<LISELOTTE<<");
+    }
+
+    #[test]
+    fn spans_extended_document_number() {
+        let mrz = "I<UTOD23145890<7349<ABC123<<<<\
+                   3407127M9507122XXA<<<<<<<<<<<3\
+                   STEVENSON<<PETER<JOHN<<<<<<<<<";
+        let raw = RawMrz::new(mrz, Format::Td1);
+
+        assert_eq!(raw.spans[0].range.start, 0);
+        assert!(raw
+            .spans
+            .windows(2)
+            .all(|pair| pair[0].range.end == pair[1].range.start));
+        assert_eq!(raw.spans.last().unwrap().range.end, 90);
+
+        let document_number = raw.field(Field::DocumentNumber).collect::<Vec<_>>();
+        assert_eq!(document_number.len(), 3);
+        assert_eq!(raw.text(document_number[0]), "D23145890<");
+        assert!(!document_number[0].check_digit);
+        assert_eq!(raw.text(document_number[1]), "734");
+        assert!(!document_number[1].check_digit);
+        assert_eq!(raw.text(document_number[2]), "9");
+        assert!(document_number[2].check_digit);
+
+        let optional_data = raw.field(Field::OptionalData).next().unwrap();
+        assert_eq!(raw.text(optional_data), "<ABC123<<<<");
+    }
+
+    #[test]
+    fn spans_extended_document_number_td2() {
+        let mrz = "I<UTOSTEVENSON<<PETER<JOHN<<<<<<<<<<\
+                   D23145890<UTO3407127M95071227349<<<8";
+        let raw = RawMrz::new(mrz, Format::Td2);
+
+        let document_number = raw.field(Field::DocumentNumber).collect::<Vec<_>>();
+        assert_eq!(document_number.len(), 3);
+        assert_eq!(document_number[0].range, 36..46);
+        assert_eq!(raw.text(document_number[1]), "734");
+        assert_eq!(raw.text(document_number[2]), "9");
+        assert!(document_number[2].check_digit);
+
+        let optional_data = raw.field(Field::OptionalData).next().unwrap();
+        assert_eq!(optional_data.range, 68..71);
+        assert_eq!(raw.text(optional_data), "<<<");
+    }
+}
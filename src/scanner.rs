@@ -0,0 +1,179 @@
+use crate::document::{Document, Format};
+use crate::error::Error;
+use crate::parser;
+use crate::validation::validate;
+
+/// Candidate Machine-readable Zone (MRZ) found in free-form text
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Candidate {
+    /// Index of the first MRZ line within the scanned text
+    pub line: usize,
+    /// MRZ lines concatenated
+    pub mrz: String,
+    /// Format implied by the number and length of the lines
+    pub format: Format,
+    /// Result of parsing the MRZ with check digit verification
+    pub document: Result<Document, Error>,
+    /// Number of check digits that verify
+    pub score: usize,
+}
+
+fn is_mrz_line(line: &str) -> bool {
+    matches!(line.len(), 30 | 36 | 44)
+        && line.contains('<')
+        && line
+            .bytes()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'<')
+}
+
+/// Find candidate Machine-readable Zones (MRZ) in free-form text such as OCR output.
+/// Consecutive lines with the length of a supported format are grouped and parsed,
+/// with candidates returned best score first and in order of appearance otherwise.
+/// Candidates sharing lines with a better one are dropped.
+pub fn find_mrz(text: &str) -> Vec<Candidate> {
+    let lines = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<String>())
+        .collect::<Vec<_>>();
+
+    let mut candidates = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if !is_mrz_line(line) {
+            continue;
+        }
+
+        let line_count = if line.len() == 30 { 3 } else { 2 };
+        let group = match lines.get(index..index + line_count) {
+            Some(group) => group,
+            None => continue,
+        };
+
+        if !group
+            .iter()
+            .all(|l| l.len() == line.len() && is_mrz_line(l))
+        {
+            continue;
+        }
+
+        let mrz = group.concat();
+        let format = match parser::format_of(&mrz) {
+            Some(format) => format,
+            None => continue,
+        };
+
+        let score = validate(&mrz).verified;
+        let document = parser::parse(&mrz, true);
+
+        candidates.push(Candidate {
+            line: index,
+            mrz,
+            format,
+            document,
+            score,
+        });
+    }
+
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.score));
+
+    let mut best: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        if !best.iter().any(|other| overlaps(other, &candidate)) {
+            best.push(candidate);
+        }
+    }
+
+    best
+}
+
+fn overlaps(a: &Candidate, b: &Candidate) -> bool {
+    a.line < b.line + b.format.lines() && b.line < a.line + a.format.lines()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn find_mrz_in_text() {
+        let text = "UTOPIA PASSPORT\n\
+                    Surname: ERIKSSON\n\
+                    Given names: ANNA MARIA\n\
+                    \n\
+                    P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n\
+                    L898902C36UTO7408122F1204159ZE184226B<<<<<10\n";
+
+        let candidates = find_mrz(text);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].line, 4);
+        assert_eq!(candidates[0].format, Format::Td3);
+        assert_eq!(candidates[0].score, 5);
+        match &candidates[0].document {
+            Ok(Document::Passport(passport)) => assert_eq!(passport.passport_number, "L898902C3"),
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn find_mrz_with_noise() {
+        let text = "ID CARD 12345\n\
+                    C<ITACA00000AA4<<<<< <<<<<<<<<<\n\
+                    6412308F2212304ITA<<<<<<<<<<<0 \n\
+                    ROSSI<<BIANCA<<<<<<<<<<<<<<<<<\n\
+                    <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<\n";
+
+        // The lines after the first one also form a (bogus) TD1 candidate
+        let candidates = find_mrz(text);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].line, 1);
+        assert_eq!(candidates[0].format, Format::Td1);
+        assert_eq!(candidates[0].score, 4);
+    }
+
+    #[test]
+    fn find_mrz_ranks_by_check_digits() {
+        let text = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n\
+                    L898902C36UTO7408122F1204159ZE184226B<<<<<11\n\
+                    \n\
+                    P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n\
+                    L898902C36UTO7408122F1204159ZE184226B<<<<<10\n";
+
+        let candidates = find_mrz(text);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].line, 3);
+        assert_eq!(candidates[0].score, 5);
+        assert_eq!(candidates[1].line, 0);
+        assert_eq!(candidates[1].score, 4);
+        assert_eq!(
+            candidates[1].document.as_ref().unwrap_err().kind(),
+            ErrorKind::BadCheckDigit
+        );
+    }
+
+    #[test]
+    fn find_mrz_ranks_by_verified_check_digits() {
+        // Document number and composite check digits are wrong in the first candidate,
+        // only the composite in the second
+        let text = "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\n\
+                    D231458908UTO7408122F1204159<<<<<<<7\n\
+                    \n\
+                    I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\n\
+                    D231458907UTO7408122F1204159<<<<<<<7\n";
+
+        let candidates = find_mrz(text);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].line, 3);
+        assert_eq!(candidates[0].score, 3);
+        assert_eq!(candidates[1].line, 0);
+        assert_eq!(candidates[1].score, 2);
+        assert!(candidates
+            .iter()
+            .all(|candidate| candidate.document.is_err()));
+    }
+
+    #[test]
+    fn find_mrz_none() {
+        assert!(find_mrz("nothing to see here\n12345").is_empty());
+    }
+}
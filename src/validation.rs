@@ -0,0 +1,242 @@
+use crate::document::{Document, DocumentCode, Field, Format, Gender, PartialDate};
+use crate::error::{Error, ErrorKind};
+use crate::parser;
+use chrono::NaiveDate;
+use std::ops::Range;
+
+/// Check digit that failed verification, or field with an invalid value
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationFailure {
+    /// Kind of failure ([`ErrorKind::BadCheckDigit`] for check digits)
+    pub kind: ErrorKind,
+    /// Field covered by the check digit, or with the invalid value
+    pub field: Field,
+    /// Characters covered by the check digit or field, as ranges of the concatenated MRZ
+    pub ranges: Vec<Range<usize>>,
+    /// Position of the check digit or offending character in the concatenated MRZ
+    pub position: usize,
+    /// Check digit calculated from the field (only for check digits)
+    pub expected: Option<u8>,
+    /// Character found in place of the check digit, or offending character
+    pub found: char,
+}
+
+impl ValidationFailure {
+    pub(crate) fn from_error(error: &Error, range: Range<usize>) -> ValidationFailure {
+        ValidationFailure {
+            kind: error.kind(),
+            field: error.field().expect("field error without field"),
+            position: error
+                .location()
+                .map_or(range.start, |location| location.offset),
+            ranges: vec![range],
+            expected: None,
+            found: error.character().unwrap_or_default(),
+        }
+    }
+}
+
+/// Fields decoded from a Machine-readable Zone (MRZ) regardless of validation failures,
+/// with those that could not be decoded left empty
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DecodedFields {
+    /// MRZ format
+    pub format: Format,
+    /// Document code
+    pub document_code: Option<DocumentCode>,
+    /// Issuing state or organization
+    pub country: String,
+    /// Surnames
+    pub surnames: Vec<String>,
+    /// Given names
+    pub given_names: Vec<String>,
+    /// Document number (passport, identity card or visa number)
+    pub document_number: String,
+    /// Nationality
+    pub nationality: String,
+    /// Date of birth, with an unknown month or day taken as the latest possible
+    /// (see [`PartialDate::latest_date`])
+    pub birth_date: Option<NaiveDate>,
+    /// Date of birth as it appears in the MRZ, where the month and day may be unknown
+    pub birth_date_partial: Option<PartialDate>,
+    /// Gender
+    pub gender: Gender,
+    /// Sex as it appears in the MRZ
+    pub sex: char,
+    /// Date of expiry
+    pub expiry_date: Option<NaiveDate>,
+    /// Optional data (personal number for passports, line 1 for TD1 identity cards)
+    pub optional_data: String,
+    /// Optional data on line 2 (TD1 only)
+    pub optional_data_2: String,
+}
+
+/// Result of validating a Machine-readable Zone (MRZ)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationReport {
+    /// Document decoded despite validation failures, unless a field could not be decoded
+    pub document: Result<Document, Error>,
+    /// Fields decoded, unless the format of the MRZ could not be determined
+    pub fields: Option<DecodedFields>,
+    /// Number of check digits that verified
+    pub verified: usize,
+    /// Every check digit that failed verification and invalid field, in MRZ order
+    pub failures: Vec<ValidationFailure>,
+}
+
+impl ValidationReport {
+    /// Whether the document was decoded and all check digits verified
+    pub fn is_valid(&self) -> bool {
+        self.document.is_ok() && self.failures.is_empty()
+    }
+}
+
+/// Validate a Machine-readable Zone (MRZ), verifying every check digit and field instead
+/// of stopping at the first failure, and decoding as many fields as possible.
+pub fn validate(data: &str) -> ValidationReport {
+    parser::validate(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_valid() {
+        let report = validate(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+        );
+        assert!(report.is_valid());
+        assert_eq!(report.verified, 5);
+        assert!(report.failures.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn validate_passport_failures() {
+        let report = validate(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7408123F1204159ZE184226B<<<<<1<",
+        );
+        assert!(!report.is_valid());
+        assert!(report.document.is_ok());
+        assert_eq!(report.verified, 3);
+        assert_eq!(
+            report.failures,
+            vec![
+                ValidationFailure {
+                    kind: ErrorKind::BadCheckDigit,
+                    field: Field::BirthDate,
+                    ranges: vec![57..63],
+                    position: 63,
+                    expected: Some(2),
+                    found: '3',
+                },
+                ValidationFailure {
+                    kind: ErrorKind::BadCheckDigit,
+                    field: Field::Composite,
+                    ranges: vec![44..54, 57..64, 65..87],
+                    position: 87,
+                    expected: Some(3),
+                    found: '<',
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_identity_card_failures() {
+        let report = validate(
+            "I<UTOD23145890<7348<<<<<<<<<<<\
+             3407127M9507123UTO<<<<<<<<<<<2\
+             STEVENSON<<PETER<JOHN<<<<<<<<<",
+        );
+        let fields = report
+            .failures
+            .iter()
+            .map(|failure| failure.field)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![Field::DocumentNumber, Field::ExpiryDate, Field::Composite]
+        );
+        assert_eq!(report.failures[0].ranges, vec![5..14, 15..18]);
+        assert_eq!(report.failures[0].position, 18);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn validate_nationality_and_sex() {
+        let report = validate(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36QQQ7408122Z1204159ZE184226B<<<<<10",
+        );
+        assert!(!report.is_valid());
+        assert_eq!(report.verified, 5);
+        assert_eq!(
+            report.failures,
+            vec![
+                ValidationFailure {
+                    kind: ErrorKind::InvalidNationality,
+                    field: Field::Nationality,
+                    ranges: vec![54..57],
+                    position: 54,
+                    expected: None,
+                    found: 'Q',
+                },
+                ValidationFailure {
+                    kind: ErrorKind::InvalidSex,
+                    field: Field::Sex,
+                    ranges: vec![64..65],
+                    position: 64,
+                    expected: None,
+                    found: 'Z',
+                },
+            ]
+        );
+
+        match report.document.unwrap() {
+            Document::Passport(passport) => {
+                assert_eq!(passport.nationality, "QQQ");
+                assert_eq!(passport.gender, Gender::Other);
+            }
+            _ => panic!("unexpected document"),
+        }
+    }
+
+    #[test]
+    fn validate_invalid_date() {
+        let report = validate(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\
+             L898902C36UTO7413122F1204159ZE184226B<<<<<10",
+        );
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.document.as_ref().unwrap_err().kind(),
+            ErrorKind::InvalidBirthDate
+        );
+        assert_eq!(report.failures[0].kind, ErrorKind::InvalidBirthDate);
+        assert_eq!(report.failures[0].position, 59);
+
+        let fields = report.fields.unwrap();
+        assert_eq!(fields.birth_date, None);
+        assert_eq!(fields.birth_date_partial, None);
+        assert_eq!(fields.expiry_date, NaiveDate::from_ymd_opt(2012, 4, 15));
+        assert_eq!(fields.document_number, "L898902C3");
+        assert_eq!(fields.surnames, vec!["ERIKSSON"]);
+        assert_eq!(fields.given_names, vec!["ANNA", "MARIA"]);
+        assert_eq!(fields.optional_data, "ZE184226B");
+    }
+
+    #[test]
+    fn validate_invalid_format() {
+        let report = validate("P<UTO");
+        assert_eq!(
+            report.document.as_ref().unwrap_err().kind(),
+            ErrorKind::InvalidFormat
+        );
+        assert_eq!(report.fields, None);
+        assert!(!report.is_valid());
+    }
+}